@@ -1,6 +1,7 @@
 pub mod primitives;
 pub mod symbol;
 pub mod type_attr;
+pub mod type_map;
 
 use syn::{parse::Parse, Token};
 
@@ -15,8 +16,6 @@ impl Parse for ContainerAttributes {
             .into_iter()
             .collect();
 
-        //panic!("LEN: {:?}", attrs);
-
         Ok(Self(attrs))
     }
 }
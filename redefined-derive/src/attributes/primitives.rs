@@ -1,4 +1,5 @@
 use proc_macro2::Ident;
+use syn::Path;
 
 pub struct Primitive(&'static str);
 
@@ -8,13 +9,13 @@ impl PartialEq<Primitive> for Ident {
     }
 }
 
-impl<'a> PartialEq<Primitive> for &'a Ident {
+impl PartialEq<Primitive> for &Ident {
     fn eq(&self, word: &Primitive) -> bool {
         *self == word.0
     }
 }
 
-impl<'a> From<Ident> for Primitive {
+impl From<Ident> for Primitive {
     fn from(value: Ident) -> Self {
         (value.to_string().as_str()).into()
     }
@@ -39,12 +40,7 @@ macro_rules! primitive {
 
         impl Primitive {
             pub fn is_primitive(ident: &Ident) -> bool {
-                match ident.to_string().as_str() {
-                    $(
-                        stringify!($v) => true,
-                    ) *
-                    _ => false
-                }
+                matches!(ident.to_string().as_str(), $(stringify!($v))|*)
             }
         }
 
@@ -54,9 +50,72 @@ macro_rules! primitive {
 primitive!(Vec);
 
 pub fn is_simple_primitive(val: &str) -> bool {
-    match val {
-        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128" | "isize" | "f32" | "f64" | "str" | "bool"
-        | "String" | "char" => true,
-        _ => false,
+    matches!(
+        val,
+        "u8" | "u16"
+            | "u32"
+            | "u64"
+            | "u128"
+            | "usize"
+            | "i8"
+            | "i16"
+            | "i32"
+            | "i64"
+            | "i128"
+            | "isize"
+            | "f32"
+            | "f64"
+            | "str"
+            | "bool"
+            | "String"
+            | "char"
+    )
+}
+
+/// std types that are kept unchanged when redefining a type, the common std
+/// types by their name (i.e. `HashMap<K, V>`, `Arc<T>`, `Duration`) and any
+/// other by it's full path (i.e. `std::num::NonZeroU64`), their generic
+/// arguments are still redefined
+///
+/// `map_types(..)` and `field(..)` are applied first, so a type sharing the
+/// name of a std type is redefined with `map_types(Duration = default)`
+pub fn is_std_type(path: &Path) -> bool {
+    match path.segments.first() {
+        Some(seg) if path.segments.len() == 1 && path.leading_colon.is_none() => is_std_name(&seg.ident.to_string()),
+        Some(seg) => matches!(seg.ident.to_string().as_str(), "std" | "core" | "alloc"),
+        None => false,
     }
 }
+
+fn is_std_name(val: &str) -> bool {
+    matches!(
+        val,
+        "Vec"
+            | "VecDeque"
+            | "LinkedList"
+            | "HashMap"
+            | "HashSet"
+            | "BTreeMap"
+            | "BTreeSet"
+            | "BinaryHeap"
+            | "Option"
+            | "Result"
+            | "Box"
+            | "Rc"
+            | "Arc"
+            | "Weak"
+            | "Cell"
+            | "RefCell"
+            | "Mutex"
+            | "RwLock"
+            | "Cow"
+            | "Pin"
+            | "PhantomData"
+            | "Duration"
+            | "Instant"
+            | "SystemTime"
+            | "PathBuf"
+            | "OsString"
+            | "CString"
+    )
+}
@@ -1,6 +1,7 @@
 use std::fmt::{self, Display};
 
-use syn::{parse::Parse, Ident, Path};
+use quote::ToTokens;
+use syn::{ext::IdentExt, parse::Parse, Ident, Path};
 
 use super::type_attr::TypeAttribute;
 
@@ -19,10 +20,29 @@ pub const TO_SOURCE_FN: Symbol = Symbol { s: "to_source", is_container: true, me
 pub const FROM_SOURCE_FN: Symbol = Symbol { s: "from_source", is_container: true, meta: SymbolMeta::NameValue };
 pub const DERIVE: Symbol = Symbol { s: "derive", is_container: true, meta: SymbolMeta::List };
 pub const OTHER_ATTR: Symbol = Symbol { s: "other", is_container: true, meta: SymbolMeta::List };
+pub const MAP_TYPES: Symbol = Symbol { s: "map_types", is_container: true, meta: SymbolMeta::List };
+pub const NAME: Symbol = Symbol { s: "name", is_container: true, meta: SymbolMeta::NameValue };
+pub const VIS: Symbol = Symbol { s: "vis", is_container: true, meta: SymbolMeta::NameValue };
+pub const FIELD_VIS: Symbol = Symbol { s: "field_vis", is_container: true, meta: SymbolMeta::NameValue };
+pub const MODULE: Symbol = Symbol { s: "module", is_container: true, meta: SymbolMeta::NameValue };
+pub const CRATE: Symbol = Symbol { s: "crate", is_container: true, meta: SymbolMeta::NameValue };
+pub const CONCRETE: Symbol = Symbol { s: "concrete", is_container: true, meta: SymbolMeta::Path };
+pub const RECURSIVE: Symbol = Symbol { s: "recursive", is_container: true, meta: SymbolMeta::Path };
+pub const MAX_DEPTH: Symbol = Symbol { s: "max_depth", is_container: true, meta: SymbolMeta::NameValue };
+pub const EXCLUDE: Symbol = Symbol { s: "exclude", is_container: true, meta: SymbolMeta::List };
+pub const SEARCH: Symbol = Symbol { s: "search", is_container: true, meta: SymbolMeta::List };
 pub const FIELD_FN: Symbol = Symbol { s: "func", is_container: false, meta: SymbolMeta::NameValue };
 pub const USE_FIELD: Symbol = Symbol { s: "field", is_container: false, meta: SymbolMeta::List };
 pub const USE_SAME_FIELDS: Symbol = Symbol { s: "same_fields", is_container: false, meta: SymbolMeta::Path };
 pub const USE_SAME_FIELD: Symbol = Symbol { s: "same", is_container: false, meta: SymbolMeta::Path };
+pub const SOURCE: Symbol = Symbol { s: "source", is_container: false, meta: SymbolMeta::NameValue };
+pub const FROM_PART: Symbol = Symbol { s: "from", is_container: false, meta: SymbolMeta::NameValue };
+
+/// container attributes only used for new types (i.e. without a source type)
+pub const NEW_TYPE_SYMBOLS: [Symbol; 5] = [MAP_TYPES, NAME, VIS, FIELD_VIS, MODULE];
+
+/// container attributes only used by `redefined_remote!`
+pub const REMOTE_SYMBOLS: [Symbol; 5] = [CONCRETE, RECURSIVE, MAX_DEPTH, EXCLUDE, SEARCH];
 
 #[derive(Copy, Debug, Clone, PartialEq)]
 pub struct Symbol {
@@ -40,43 +60,129 @@ impl Symbol {
         vals.iter().find(|val| val.symbol.s == self.s).cloned()
     }
 
-    pub fn illegal_pairings(symbols: &[Symbol], has_source_type: bool) {
+    /// errors on container attributes that can't be used together, spanned at
+    /// the `#[redefined_attr(...)]` attribute
+    pub fn illegal_pairings(symbols: &[Symbol], has_source_type: bool, attr: &impl ToTokens) -> syn::Result<()> {
         #[cfg(feature = "unsafe")]
-        if symbols.contains(&TRANSMUTE) && symbols.len() > 1 {
-            panic!("Cannot have transmute attribute with other container attributes: {:?}", symbols);
+        if symbols.contains(&TRANSMUTE)
+            && symbols
+                .iter()
+                .any(|symbol| ![TRANSMUTE, CRATE].contains(symbol))
+        {
+            return Err(syn::Error::new_spanned(
+                attr,
+                format!("Cannot have transmute attribute with other container attributes: {}", join_symbols(symbols)),
+            ));
+        }
+
+        if let Some(symbol) = REMOTE_SYMBOLS
+            .iter()
+            .find(|symbol| symbols.contains(symbol))
+        {
+            return Err(syn::Error::new_spanned(attr, format!("The `{symbol}` attribute can only be used with `redefined_remote!`")));
         }
 
         if (symbols.contains(&FROM_SOURCE_FN) || symbols.contains(&TO_SOURCE_FN)) && !has_source_type {
-            panic!("Cannot have to/from attributes without a source type: {:?}", symbols);
+            return Err(syn::Error::new_spanned(attr, format!("Cannot have to/from attributes without a source type: {}", join_symbols(symbols))));
         }
+
+        if let Some(symbol) = NEW_TYPE_SYMBOLS
+            .iter()
+            .find(|symbol| symbols.contains(symbol))
+        {
+            if has_source_type {
+                return Err(syn::Error::new_spanned(attr, format!("Cannot have the `{symbol}` attribute with a source type")));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// all attribute symbols
+    fn all() -> Vec<Symbol> {
+        let mut symbols = vec![
+            TO_SOURCE_FN,
+            FROM_SOURCE_FN,
+            DERIVE,
+            OTHER_ATTR,
+            MAP_TYPES,
+            NAME,
+            VIS,
+            FIELD_VIS,
+            MODULE,
+            CRATE,
+            CONCRETE,
+            RECURSIVE,
+            MAX_DEPTH,
+            EXCLUDE,
+            SEARCH,
+            FIELD_FN,
+            USE_FIELD,
+            USE_SAME_FIELDS,
+            USE_SAME_FIELD,
+            SOURCE,
+            FROM_PART,
+        ];
+
+        #[cfg(feature = "unsafe")]
+        symbols.push(TRANSMUTE);
+
+        symbols
+    }
+
+    /// the symbol closest to a misspelled attribute name
+    fn closest(value: &str) -> Option<Symbol> {
+        Self::all()
+            .into_iter()
+            .map(|symbol| (edit_distance(value, symbol.s), symbol))
+            .filter(|(distance, symbol)| *distance <= 2.max(symbol.s.len() / 3))
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, symbol)| symbol)
     }
 }
 
+fn join_symbols(symbols: &[Symbol]) -> String {
+    symbols
+        .iter()
+        .map(|s| format!("`{s}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev_row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = prev_row[j] + usize::from(a_char != *b_char);
+            row.push(substitution.min(prev_row[j + 1] + 1).min(row[j] + 1));
+        }
+        prev_row = row;
+    }
+
+    prev_row[b.len()]
+}
+
 impl Parse for Symbol {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let ident: Ident = input.parse()?;
+        // `crate` is a keyword
+        let ident = input.call(Ident::parse_any)?;
         let binding = ident.to_string();
         let s = binding.as_str();
 
-        Ok(s.into())
-    }
-}
-
-impl From<&str> for Symbol {
-    fn from(value: &str) -> Self {
-        match value {
-            #[cfg(feature = "unsafe")]
-            "transmute" => TRANSMUTE,
-            "to_source" => TO_SOURCE_FN,
-            "from_source" => FROM_SOURCE_FN,
-            "func" => FIELD_FN,
-            "field" => USE_FIELD,
-            "derive" => DERIVE,
-            "same" => USE_SAME_FIELD,
-            "other" => OTHER_ATTR,
-            "same_fields" => USE_SAME_FIELDS,
-            _ => panic!("No attribute for {}", value),
+        if let Some(symbol) = Self::all().into_iter().find(|symbol| symbol.s == s) {
+            return Ok(symbol);
         }
+
+        let msg = match Self::closest(s) {
+            Some(closest) => format!("No attribute for `{s}`, did you mean `{closest}`?"),
+            None => format!("No attribute for `{s}`"),
+        };
+
+        Err(syn::Error::new_spanned(ident, msg))
     }
 }
 
@@ -86,7 +192,7 @@ impl PartialEq<Symbol> for Ident {
     }
 }
 
-impl<'a> PartialEq<Symbol> for &'a Ident {
+impl PartialEq<Symbol> for &Ident {
     fn eq(&self, word: &Symbol) -> bool {
         *self == word.s
     }
@@ -98,7 +204,7 @@ impl PartialEq<Symbol> for Path {
     }
 }
 
-impl<'a> PartialEq<Symbol> for &'a Path {
+impl PartialEq<Symbol> for &Path {
     fn eq(&self, word: &Symbol) -> bool {
         self.is_ident(word.s)
     }
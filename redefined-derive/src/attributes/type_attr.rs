@@ -2,17 +2,18 @@ use std::fmt::Debug;
 
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
-use syn::{self, parenthesized, parse::Parse, parse_quote, Expr, Ident, LitStr, Token, TypeTuple};
+use syn::{self, parenthesized, parse::Parse, Expr, ExprLit, ExprPath, Ident, Lit, Path, Token, Type, TypeTuple};
 
 use super::symbol::*;
 
 #[derive(Clone)]
 pub struct TypeAttribute {
-    pub symbol:            Symbol,
-    pub nv_tokens:         Option<Expr>,
-    pub list_idents:       Option<Vec<Ident>>,
-    pub list_tuple_idents: Option<Vec<(Ident, Ident)>>,
-    pub list_other_attrs:  Option<Vec<TokenStream>>,
+    pub symbol:           Symbol,
+    pub nv_tokens:        Option<Expr>,
+    pub list_idents:      Option<Vec<Ident>>,
+    pub list_type_pairs:  Option<Vec<(Path, Path)>>,
+    pub list_paths:       Option<Vec<Path>>,
+    pub list_other_attrs: Option<Vec<TokenStream>>,
 }
 
 impl PartialEq for TypeAttribute {
@@ -37,15 +38,84 @@ impl Debug for TypeAttribute {
     }
 }
 
+impl TypeAttribute {
+    /// whether a `source = ..` attribute refers to the given source type,
+    /// either by it's full path or by the identifier of it's last segment,
+    /// with the same generic arguments if it has any
+    ///
+    /// errors if it refers to several of the source types of the target (i.e.
+    /// `source = Compression` with `v1::Compression` and `v2::Compression`)
+    pub fn matches_source(&self, source_type: &Path, all_source_types: &[Path]) -> syn::Result<bool> {
+        let Some(Expr::Path(attr_source)) = &self.nv_tokens else { return Ok(false) };
+        let attr_source = &attr_source.path;
+
+        let mut sources = Vec::<&Path>::new();
+        for source in all_source_types {
+            if !sources.iter().any(|other| same_path(other, source)) {
+                sources.push(source);
+            }
+        }
+
+        // sources matching by their full path take precedence over those matching by
+        // their last segment
+        let full_path_matched = sources
+            .iter()
+            .copied()
+            .filter(|source| source_match(attr_source, source) == Some(SourceMatch::FullPath))
+            .collect::<Vec<_>>();
+        let matched = if full_path_matched.is_empty() {
+            sources
+                .into_iter()
+                .filter(|source| source_match(attr_source, source).is_some())
+                .collect()
+        } else {
+            full_path_matched
+        };
+
+        if matched.len() > 1 {
+            let matched = matched
+                .iter()
+                .map(|source| format!("`{}`", source.to_token_stream().to_string().replace(' ', "")))
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(syn::Error::new_spanned(
+                attr_source,
+                format!(
+                    "Ambiguous source `{}`, it matches {matched}, use it's full path",
+                    attr_source.to_token_stream().to_string().replace(' ', "")
+                ),
+            ));
+        }
+
+        Ok(matched
+            .first()
+            .is_some_and(|source| same_path(source, source_type)))
+    }
+
+    /// parses the string value of an attribute (i.e. `vis = "pub(crate)"`)
+    pub fn parse_nv_str<T: Parse>(&self) -> syn::Result<T> {
+        match &self.nv_tokens {
+            Some(Expr::Lit(ExprLit { lit: Lit::Str(lit_nv), .. })) => lit_nv.parse(),
+            other => Err(syn::Error::new_spanned(other, format!("Expected a string value for `{}`", self.symbol))),
+        }
+    }
+
+    /// the index of a `from = ..` attribute
+    pub fn part_index(&self) -> syn::Result<usize> {
+        match &self.nv_tokens {
+            Some(Expr::Lit(ExprLit { lit: Lit::Int(idx), .. })) => idx.base10_parse(),
+            other => Err(syn::Error::new_spanned(other, "Expected an integer index of the source part")),
+        }
+    }
+}
+
 impl Parse for TypeAttribute {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let symbol: Symbol = input.parse()?;
 
         let this = match symbol.meta {
-            SymbolMeta::Path => Self { symbol, nv_tokens: None, list_idents: None, list_tuple_idents: None, list_other_attrs: None },
+            SymbolMeta::Path => Self { symbol, nv_tokens: None, list_idents: None, list_type_pairs: None, list_paths: None, list_other_attrs: None },
             SymbolMeta::List => {
-                //let t = input.parse::<Ident>()?;
-                // panic!("NONONO, {}", t);
                 let content;
                 parenthesized!(content in input);
 
@@ -56,53 +126,119 @@ impl Parse for TypeAttribute {
                         .map(|stream| quote!(#stream ))
                         .collect::<Vec<_>>();
 
-                    // panic!("SYMBOL:\n{:?}",
-                    // other_container_attrs.first().to_token_stream().to_string());
-
-                    Self { symbol, nv_tokens: None, list_idents: None, list_tuple_idents: None, list_other_attrs: Some(other_container_attrs) }
+                    Self {
+                        symbol,
+                        nv_tokens: None,
+                        list_idents: None,
+                        list_type_pairs: None,
+                        list_paths: None,
+                        list_other_attrs: Some(other_container_attrs),
+                    }
+                } else if symbol == MAP_TYPES {
+                    let pairs = content
+                        .parse_terminated(
+                            |input| {
+                                let source = input.parse::<Path>()?;
+                                input.parse::<Token![=]>()?;
+                                Ok((source, input.parse::<Path>()?))
+                            },
+                            Token![,],
+                        )?
+                        .into_iter()
+                        .collect();
+                    Self { symbol, nv_tokens: None, list_idents: None, list_type_pairs: Some(pairs), list_paths: None, list_other_attrs: None }
+                } else if symbol == EXCLUDE {
+                    let paths = content
+                        .parse_terminated(Path::parse_mod_style, Token![,])?
+                        .into_iter()
+                        .collect();
+                    Self { symbol, nv_tokens: None, list_idents: None, list_type_pairs: None, list_paths: Some(paths), list_other_attrs: None }
                 } else if content.peek(syn::Ident) {
                     let idents = content
                         .parse_terminated(Ident::parse, Token![,])?
                         .into_iter()
                         .collect();
-                    Self { symbol, nv_tokens: None, list_idents: Some(idents), list_tuple_idents: None, list_other_attrs: None }
+                    Self { symbol, nv_tokens: None, list_idents: Some(idents), list_type_pairs: None, list_paths: None, list_other_attrs: None }
                 } else {
-                    let idents = content
+                    let pairs = content
                         .parse_terminated(TypeTuple::parse, Token![,])?
                         .into_iter()
-                        .map(|mut tupl| {
-                            let ident1 = match tupl
-                                .elems
-                                .pop()
-                                .expect("Tuple argments must have 2")
-                                .into_value()
-                            {
-                                syn::Type::Path(p) => p.path.get_ident().unwrap().clone(),
-                                _ => unreachable!("Tuple Ident 2 must be a path"),
-                            };
-                            let ident0 = match tupl
-                                .elems
-                                .pop()
-                                .expect("Tuple argments must have 2")
-                                .into_value()
-                            {
-                                syn::Type::Path(p) => p.path.get_ident().unwrap().clone(),
-                                _ => unreachable!("Tuple Ident 2 must be a path"),
-                            };
-                            (ident0, ident1)
+                        .map(|tupl| {
+                            if tupl.elems.len() != 2 {
+                                return Err(syn::Error::new_spanned(tupl, "Expected a tuple of 2 types: `(<SOURCE TYPE>, <TARGET TYPE>)`"));
+                            }
+
+                            Ok((tuple_path(&tupl.elems[0])?, tuple_path(&tupl.elems[1])?))
                         })
-                        .collect::<Vec<_>>();
-                    Self { symbol, nv_tokens: None, list_idents: None, list_tuple_idents: Some(idents), list_other_attrs: None }
+                        .collect::<syn::Result<Vec<_>>>()?;
+                    Self { symbol, nv_tokens: None, list_idents: None, list_type_pairs: Some(pairs), list_paths: None, list_other_attrs: None }
                 }
             }
             SymbolMeta::NameValue => {
                 input.parse::<Token![=]>()?;
-                let nv = input.parse::<Expr>()?;
-                let lit_nv: LitStr = parse_quote!(#nv);
-                Self { symbol, nv_tokens: Some(lit_nv.parse()?), list_idents: None, list_tuple_idents: None, list_other_attrs: None }
+                // source types can have generic arguments (i.e. `source = Uint<256, 4>`),
+                // which can't be parsed as an expression
+                let nv = if symbol == SOURCE {
+                    Expr::Path(ExprPath { attrs: Vec::new(), qself: None, path: input.parse()? })
+                } else {
+                    input.parse::<Expr>()?
+                };
+                // string values are parsed into expressions, others (i.e. `source = A`,
+                // `from = 0`), visibilities, module names and crate paths are used as is
+                let nv_tokens = match nv {
+                    Expr::Lit(ExprLit { lit: Lit::Str(lit_nv), .. }) if ![VIS, FIELD_VIS, MODULE, CRATE].contains(&symbol) => lit_nv.parse()?,
+                    nv => nv,
+                };
+                Self { symbol, nv_tokens: Some(nv_tokens), list_idents: None, list_type_pairs: None, list_paths: None, list_other_attrs: None }
             }
         };
 
         Ok(this)
     }
 }
+
+/// how a `source = ..` attribute refers to a source type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SourceMatch {
+    /// `source = v1::Compression` for `v1::Compression`
+    FullPath,
+    /// `source = Compression` for `v1::Compression`
+    LastSegment,
+}
+
+fn same_path(a: &Path, b: &Path) -> bool {
+    a.to_token_stream().to_string() == b.to_token_stream().to_string()
+}
+
+fn source_match(attr_source: &Path, source_type: &Path) -> Option<SourceMatch> {
+    let idents = |path: &Path| {
+        path.segments
+            .iter()
+            .map(|seg| seg.ident.to_string())
+            .collect::<Vec<_>>()
+    };
+    let (attr_idents, source_idents) = (idents(attr_source), idents(source_type));
+
+    // generic arguments only have to match if the attribute has them
+    let attr_args = &attr_source.segments.last()?.arguments;
+    let source_args = &source_type.segments.last()?.arguments;
+    if !attr_args.is_empty() && attr_args.to_token_stream().to_string() != source_args.to_token_stream().to_string() {
+        return None;
+    }
+
+    if attr_idents == source_idents {
+        Some(SourceMatch::FullPath)
+    } else if attr_idents.len() == 1 && attr_idents.last() == source_idents.last() {
+        Some(SourceMatch::LastSegment)
+    } else {
+        None
+    }
+}
+
+/// the path of a type in a `field((<SOURCE TYPE>, <TARGET TYPE>))` tuple
+fn tuple_path(ty: &Type) -> syn::Result<Path> {
+    match ty {
+        Type::Path(p) if p.qself.is_none() => Ok(p.path.clone()),
+        _ => Err(syn::Error::new_spanned(ty, "Expected a type path")),
+    }
+}
@@ -0,0 +1,66 @@
+use quote::ToTokens;
+use syn::{Path, PathArguments};
+
+use super::{symbol::MAP_TYPES, type_attr::TypeAttribute};
+
+/// source types mapped to the types used in a new type, from the field
+/// attribute `field((<SOURCE TYPE>, <TARGET TYPE>))` or the container attribute
+/// `map_types(<SOURCE TYPE> = <TARGET TYPE>)`
+#[derive(Clone, Default)]
+pub struct TypeMap(Vec<(Path, Path)>);
+
+impl TypeMap {
+    pub fn new(pairs: Vec<(Path, Path)>) -> Self {
+        Self(pairs)
+    }
+
+    /// the mappings of all `map_types(..)` container attributes
+    pub fn from_container_attrs(attrs: &[TypeAttribute]) -> Self {
+        Self(
+            attrs
+                .iter()
+                .filter(|attr| attr.symbol == MAP_TYPES)
+                .filter_map(|attr| attr.list_type_pairs.clone())
+                .flatten()
+                .collect(),
+        )
+    }
+
+    /// adds the mappings of `other` after this map's, so this map's take
+    /// precedence
+    pub fn with_fallback(mut self, other: &TypeMap) -> Self {
+        self.0.extend(other.0.iter().cloned());
+        self
+    }
+
+    /// the first mapping matching a path, either by it's full path or the
+    /// trailing segments (i.e. `B256` or `alloy::B256` match
+    /// `alloy::B256`), a source type with generic arguments only matches the
+    /// same arguments
+    pub fn get(&self, path: &Path) -> Option<&(Path, Path)> {
+        self.0.iter().find(|(source, _)| matches_path(source, path))
+    }
+}
+
+fn matches_path(source: &Path, path: &Path) -> bool {
+    let (source_len, path_len) = (source.segments.len(), path.segments.len());
+    if source_len > path_len || (source.leading_colon.is_some() && source_len != path_len) {
+        return false;
+    }
+
+    let segments_match = source
+        .segments
+        .iter()
+        .zip(path.segments.iter().skip(path_len - source_len))
+        .all(|(source_seg, path_seg)| source_seg.ident == path_seg.ident);
+
+    let args_match = match (source.segments.last(), path.segments.last()) {
+        (Some(source_seg), Some(path_seg)) => match &source_seg.arguments {
+            PathArguments::None => true,
+            args => args.to_token_stream().to_string() == path_seg.arguments.to_token_stream().to_string(),
+        },
+        _ => false,
+    };
+
+    segments_match && args_match
+}
@@ -0,0 +1,208 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use serde::Deserialize;
+use syn::{parse::Parser, parse_quote, Attribute, Ident};
+
+use crate::attributes::type_map::TypeMap;
+
+/// the name of the config file, in the crate's directory or one of it's
+/// ancestors up to the workspace's (next to `Cargo.lock`)
+pub const CONFIG_FILE: &str = "redefined.toml";
+
+/// the last loaded config, reloaded when the file is modified
+static CACHED_CONFIG: Mutex<Option<(PathBuf, Option<SystemTime>, Config)>> = Mutex::new(None);
+
+/// the crates and config files `track_tokens` has included the file for
+static TRACKED_CONFIGS: Mutex<Vec<(String, PathBuf)>> = Mutex::new(Vec::new());
+
+/// crate-wide configuration from a `redefined.toml`
+///
+/// ```toml
+/// crate = "::facade::redefined"
+/// prefix = ""
+/// suffix = "Mirror"
+/// derives = ["Debug", "Clone"]
+/// attributes = ["#[allow(dead_code)]"]
+///
+/// [map_types]
+/// "alloy::B256" = "same"
+/// Uint = "U256Mirror"
+///
+/// [remote]
+/// transmute = true
+/// cargo_home = "/path/to/.cargo"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// the path of the `redefined` crate in generated code (defaults to
+    /// `::redefined`), the same as the `crate = ".."` container attribute
+    #[serde(rename = "crate")]
+    pub crate_path: Option<String>,
+    /// prepended to the names of new types
+    pub prefix:     String,
+    /// appended to the names of new types (defaults to `Redefined`)
+    pub suffix:     String,
+    /// derives added to every new type
+    pub derives:    Vec<String>,
+    /// attributes added to every new type (i.e. `"#[allow(dead_code)]"`)
+    pub attributes: Vec<String>,
+    /// type mappings applied to every new type, the same as the `map_types(..)`
+    /// container attribute with a lower precedence
+    pub map_types:  BTreeMap<String, String>,
+    pub remote:     RemoteConfig,
+    /// the path of the config file
+    #[serde(skip)]
+    pub path:       Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            crate_path: None,
+            prefix:     String::new(),
+            suffix:     "Redefined".to_string(),
+            derives:    Vec::new(),
+            attributes: Vec::new(),
+            map_types:  BTreeMap::new(),
+            remote:     RemoteConfig::default(),
+            path:       None,
+        }
+    }
+}
+
+/// options for `redefined_remote!`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RemoteConfig {
+    /// converts remote types with transmute, unless both `to_source` and
+    /// `from_source` are given (defaults to `true`)
+    pub transmute:  bool,
+    /// the cargo home to find the sources of remote packages in (defaults to
+    /// `$CARGO_HOME` or `~/.cargo`)
+    pub cargo_home: Option<PathBuf>,
+}
+
+impl Default for RemoteConfig {
+    fn default() -> Self {
+        Self { transmute: true, cargo_home: None }
+    }
+}
+
+impl Config {
+    /// loads the closest `redefined.toml` to the crate being compiled, or the
+    /// default config if there is none
+    pub fn load() -> syn::Result<Self> {
+        let Some(path) = config_path() else { return Ok(Self::default()) };
+        let modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+
+        let mut cached = CACHED_CONFIG.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((cached_path, cached_modified, config)) = cached.as_ref() {
+            if *cached_path == path && *cached_modified == modified {
+                return Ok(config.clone());
+            }
+        }
+
+        let config_err = |e: &dyn std::fmt::Display| syn::Error::new(Span::call_site(), format!("Failed to read {}: {e}", path.display()));
+        let contents = std::fs::read_to_string(&path).map_err(|e| config_err(&e))?;
+        let mut config: Config = toml::from_str(&contents).map_err(|e| config_err(&e))?;
+        config.path = Some(path.clone());
+
+        *cached = Some((path, modified, config.clone()));
+
+        Ok(config)
+    }
+
+    /// the name of the new type of a type (i.e. `BasicStruct` ->
+    /// `BasicStructRedefined`)
+    pub fn redefined_name(&self, ident: &Ident) -> Ident {
+        Ident::new(&format!("{}{ident}{}", self.prefix, self.suffix), ident.span())
+    }
+
+    /// the default derives and attributes of new types, as attributes
+    pub fn new_type_attrs(&self) -> syn::Result<Vec<Attribute>> {
+        let mut attrs = Vec::new();
+        if !self.derives.is_empty() {
+            let derives = self.parse_derives()?;
+            attrs.push(parse_quote!(#[redefined_attr(derive(#(#derives),*))]));
+        }
+
+        for attr in &self.attributes {
+            attrs.extend(Attribute::parse_outer.parse_str(attr)?);
+        }
+
+        Ok(attrs)
+    }
+
+    /// the default derives of new types
+    pub fn parse_derives(&self) -> syn::Result<Vec<Ident>> {
+        self.derives
+            .iter()
+            .map(|derive| syn::parse_str(derive))
+            .collect()
+    }
+
+    /// the path of the `redefined` crate, if set
+    pub fn crate_path(&self) -> syn::Result<Option<syn::Path>> {
+        self.crate_path.as_deref().map(syn::parse_str).transpose()
+    }
+
+    /// the global type mappings
+    pub fn type_map(&self) -> syn::Result<TypeMap> {
+        let pairs = self
+            .map_types
+            .iter()
+            .map(|(source, target)| Ok((syn::parse_str(source)?, syn::parse_str(target)?)))
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        Ok(TypeMap::new(pairs))
+    }
+
+    /// includes the config file in the expanded tokens, so changes to it
+    /// recompile the crate
+    ///
+    /// the file is included once per crate, by the first expansion using it,
+    /// since each crate is compiled by a new process of the compiler
+    pub fn track_tokens(&self) -> TokenStream {
+        let Some(path) = &self.path else { return TokenStream::new() };
+
+        let crate_name = std::env::var("CARGO_CRATE_NAME").unwrap_or_default();
+        let mut tracked = TRACKED_CONFIGS.lock().unwrap_or_else(|e| e.into_inner());
+        if tracked.contains(&(crate_name.clone(), path.clone())) {
+            return TokenStream::new();
+        }
+        tracked.push((crate_name, path.clone()));
+
+        let Some(path) = path.to_str() else { return TokenStream::new() };
+        quote! {
+            const _: &[u8] = include_bytes!(#path);
+        }
+    }
+}
+
+/// the path of the closest config file, in the manifest dir of the crate being
+/// compiled or it's ancestors up to the workspace's (with the `Cargo.lock`)
+fn config_path() -> Option<PathBuf> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").ok()?;
+
+    for dir in Path::new(&manifest_dir).ancestors() {
+        let path = dir.join(CONFIG_FILE);
+        if path.is_file() {
+            return Some(path);
+        }
+        if dir.join("Cargo.lock").is_file() {
+            break;
+        }
+    }
+
+    None
+}
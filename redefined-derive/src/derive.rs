@@ -1,20 +1,36 @@
 use proc_macro2::TokenStream;
+use quote::quote;
 use syn::DeriveInput;
 
-use crate::{new_types::parse_type_without_source, outer::OuterContainer, redefined_types::RedefinedContainer};
+use crate::{config::Config, new_types::parse_type_without_source, outer::OuterContainer, redefined_types::RedefinedContainer};
 
 pub fn expand_derive_redefined(input: &DeriveInput, is_remote: bool) -> syn::Result<TokenStream> {
     let outer = OuterContainer::parse(input.clone().ident, &input.attrs)?;
 
-    let derive_tokens = if outer.source_type.is_none() {
+    let derive_tokens = if !outer.has_source_types() {
         parse_type_without_source(outer, input, is_remote)?
     } else {
-        let container = RedefinedContainer::parse_sub_containers(outer, &input.data, &input.generics)?;
-        let tokens = container.finalize();
+        let tokens = outer
+            .source_types
+            .iter()
+            .map(|source_type| {
+                let container = RedefinedContainer::parse_sub_containers(&outer, source_type, &input.data, &input.generics)?;
+                Ok(container.finalize())
+            })
+            .chain(outer.composed_types.iter().map(|source_parts| {
+                let container = RedefinedContainer::parse_composed(&outer, source_parts, &input.data, &input.generics)?;
+                Ok(container.finalize())
+            }))
+            .collect::<syn::Result<Vec<_>>>()?;
 
-        // panic!("NEW TYPE: \n{}", tokens.to_string());
-        tokens
+        quote!(#(#tokens)*)
     };
 
-    Ok(derive_tokens)
+    // remote types are tracked by `redefined_remote!`
+    if is_remote {
+        return Ok(derive_tokens);
+    }
+
+    let config_tokens = Config::load()?.track_tokens();
+    Ok(quote!(#derive_tokens #config_tokens))
 }
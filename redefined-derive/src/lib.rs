@@ -11,6 +11,10 @@ mod redefined_types;
 
 mod attributes;
 
+mod config;
+
+mod registry;
+
 mod outer;
 
 mod new_types;
@@ -21,9 +25,22 @@ mod remote;
 /// # DERIVE MACRO
 ///
 /// ## Container Source Attribute
-/// WITH: `#[redefined(<TYPE IDENTIFIER>)]`
+/// WITH: `#[redefined(<TYPE PATH>)]`
 ///     - Only used to specify the source struct (i.e. the struct converting
 ///       from)
+///     - Can be a bare identifier or a full path with generic arguments (i.e.
+///       `other::BasicStruct` or `ruint::Uint<256, 4>`), a path with generic
+///       arguments is used exactly as written in the generated impls
+///     - Generic arguments can instantiate the source for a target with fewer
+///       (or no) generic parameters, in any order (i.e.
+///       `#[redefined(GenericTypeStruct<u128, Y>)]` on `PoolState<Y>`)
+///     - Multiple source types can be given as a comma seperated list or in
+///       repeated attributes (i.e. `#[redefined(v1::Compression,
+///       v2::Compression)]`), a full set of impls is generated for each
+///     - A tuple of source types composes the target from several source values
+///       (i.e. `#[redefined((PoolConfig, PoolState))]`), implementing
+///       `RedefinedConvert<(PoolConfig, PoolState)>`, where each field is
+///       converted from the part given by `#[redefined(from = ..)]`
 ///
 /// ### Example:
 /// ```ignore
@@ -36,6 +53,16 @@ mod remote;
 ///     }
 /// ```
 ///
+/// ```ignore
+///     #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+///     #[redefined(GenericTypeStruct<u128, String>)]
+///     pub struct PoolStateRedefined {
+///         pub p:    u64,
+///         pub d:    u128,
+///         pub vals: Vec<String>,
+///     }
+/// ```
+///
 /// ---
 /// WITHOUT: `#[redefined(<TYPE IDENTIFIER>)]`
 ///     - Omitted when the current type is the source type to a new type
@@ -43,6 +70,15 @@ mod remote;
 ///       (except redefined attributes)
 ///     - The new type's name will be the same as this type's name with a
 ///       concatinated `Redefined`
+///     - Field types are redefined by renaming the last segment of their path
+///       (i.e. `inner::PathInner` -> `inner::PathInnerRedefined`), the path of
+///       another crate is dropped (i.e. `ruint::Uint<256, 4>` ->
+///       `UintRedefined<256, 4>`)
+///     - Primitives, std types (`Vec`, `Option`, `HashMap`, `Arc`,
+///       `PhantomData`, `Duration`, .. or any by it's full path, i.e.
+///       `std::num::NonZeroU64`), generic parameters and qualified paths (i.e.
+///       `<T as Trait>::Assoc`) are kept unchanged. A type sharing the name of
+///       a std type is redefined with `map_types(Duration = default)`
 ///     - The new type implements from/into this type via transmute
 ///     - TODO: other methods besides transmute
 ///
@@ -60,6 +96,35 @@ mod remote;
 ///
 ///
 /// ---
+/// ## Configuration
+/// A `redefined.toml` in the crate's directory, or the closest one in it's
+/// ancestors up to the workspace's (next to `Cargo.lock`), sets crate-wide
+/// defaults, changes to it recompile the crate
+///
+/// ```toml
+///     # the path of the crate in generated code, see `crate = "..."` below
+///     crate = "::facade::redefined"
+///     # the new type's name is `<prefix><TYPE><suffix>` (defaults to `Redefined`)
+///     prefix = ""
+///     suffix = "Redefined"
+///     # derives and attributes added to every new type
+///     derives = ["Debug", "Clone"]
+///     attributes = ["#[allow(dead_code)]"]
+///
+///     # type mappings for every new type, see `map_types(...)` below
+///     [map_types]
+///     "alloy::B256" = "same"
+///     "Uint<256, 4>" = "U256Mirror"
+///
+///     [remote]
+///     # converts remote types with transmute (defaults to `true`)
+///     transmute = true
+///     # where the sources of remote packages are found
+///     cargo_home = "/path/to/.cargo"
+/// ```
+///
+///
+/// ---
 /// ## Container Attributes
 /// `#[redefined_attr(...)]`
 ///
@@ -82,6 +147,57 @@ mod remote;
 /// 4) `derive(...)`
 ///     - specifies `#[derive(...)]` values for the new type
 ///
+/// 5) `map_types(...)`
+///     - maps field types of every field and variant of the new type
+///     - syntax: comma seperated list of `<SOURCE TYPE> = <TARGET TYPE>`, where
+///       the types can be full paths with generic arguments (i.e. `alloy::B256
+///       = same`, `Uint<256, 4> = U256Mirror`), and the target type can be
+///       `same` or `default` as in the `field(...)` field attribute
+///     - the field attribute `field(...)` takes precedence
+///     - NOTE: only used when the container attribute `redefined(...)` is
+///       omitted
+///
+/// 6) `name = "..."`
+///     - the name of the new type, instead of `<TYPE>Redefined`
+///     - types expanded after it in the same crate use the name for fields of
+///       this type, otherwise map it with `map_types(...)`. Types are only
+///       known by their identifier, so a field type matching types with
+///       different names in other modules is an error and must be mapped. The
+///       names are recorded while the crate is expanded, so tools keeping the
+///       macros loaded (i.e. rust-analyzer) can see outdated names
+///     - NOTE: only used when the container attribute `redefined(...)` is
+///       omitted
+///
+/// 7) `vis = "..."` / `field_vis = "..."`
+///     - the visibility of the new type (i.e. `vis = "pub(crate)"`) and of
+///       every field of the new type (i.e. `field_vis = "pub"`), instead of the
+///       visibilities of this type
+///     - NOTE: only used when the container attribute `redefined(...)` is
+///       omitted, `field_vis` can't be used on enums
+///
+/// 8) `module = "..."`
+///     - generates the new type and it's impls in a module (i.e. `module =
+///       "mirror"` -> `mirror::BasicStructRedefined`) with the visibility of
+///       the new type, the module sees the items of the calling module
+///     - visibilities keep their meaning from the calling module (i.e. a
+///       private type or field is `pub(super)` in the module)
+///     - types expanded after it in the same crate reference the new type by
+///       the path of this type with the module before the name (i.e.
+///       `other::BasicStruct` -> `other::mirror::BasicStructRedefined`)
+///     - NOTE: only used when the container attribute `redefined(...)` is
+///       omitted, a module can only be generated once so each type needs it's
+///       own module, use `redefined_remote!(mod <MODULE> { .. })` to generate
+///       several remote types in one module
+///
+/// 9) `crate = "..."`
+///     - the path of this crate in generated code (defaults to `::redefined`),
+///       i.e. when it's renamed in `Cargo.toml` or re-exported from another
+///       crate (`crate = "::facade::redefined"`)
+///     - new types derive `Redefined` through the path
+///     - the generated code only uses absolute paths (`::core`) and generic
+///       parameters or bindings that can't collide with the type's own
+///       identifiers, except for `src` (see `from_source`)
+///
 /// ### Examples:
 /// `to_source = '..'` + `from_source = '..'`
 /// ```ignore
@@ -121,6 +237,59 @@ mod remote;
 ///     }
 /// ```
 ///
+/// `name`
+/// ```ignore
+///     #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+///     #[redefined_attr(name = "NamedInnerMirror")]
+///     pub struct NamedInner {
+///         pub val: u64,
+///     }
+///     // NamedInnerMirror is created
+/// ```
+///
+/// `vis` + `field_vis`
+/// ```ignore
+///     #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+///     #[redefined_attr(vis = "pub(crate)", field_vis = "pub")]
+///     pub struct PrivateFields {
+///         val: u64,
+///     }
+///     // the new type will be:
+///     pub(crate) struct PrivateFieldsRedefined {
+///         pub val: u64,
+///     }
+/// ```
+///
+/// `module`
+/// ```ignore
+///     #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+///     #[redefined_attr(module = "mirror")]
+///     pub struct ModuleInner {
+///         pub val: u64,
+///     }
+///     // the new type will be:
+///     pub mod mirror {
+///         use super::*;
+///
+///         pub struct ModuleInnerRedefined {
+///             pub val: u64,
+///         }
+///     }
+/// ```
+///
+/// `map_types`
+/// ```ignore
+///     #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+///     #[redefined_attr(map_types(Address = same, Amount<2> = AmountMirror))]
+///     pub struct Account {
+///         pub address:  Address,
+///         pub balances: Vec<Amount<2>>,
+///     }
+///     // the new type will have fields:
+///     pub address:  Address,
+///     pub balances: Vec<AmountMirror>,
+/// ```
+///
 ///
 /// ---
 /// ## Field Attributes
@@ -136,15 +305,20 @@ mod remote;
 /// 2) `field(...)`
 ///     - specifies a type to use when converting between target and source
 ///       types
-///     - syntax: comma seperated list of `(<SOURCE TYPE>, <TARGET TYPE>)`
+///     - syntax: comma seperated list of `(<SOURCE TYPE>, <TARGET TYPE>)`,
+///       where the types can be full paths with generic arguments
 ///     - NOTE: only used when the container attribute `redefined(...)` is
 ///       omitted
-///     - **Sub-attributes**: 1) `field((<SOURCE TYPE>, same))`
+///     - **Sub-attributes**:
+///         1) `field((<SOURCE TYPE>, same))`
 ///             - `same` can be used in place of the target type, when the
 ///               target type's identifier is the same as the source types
 ///         2) `field((<SOURCE TYPE>, <TARGET TYPE>))`
 ///             - used when you want to convert the `<SOURCE TYPE>` to a custom
 ///               `<TARGET TYPE>`
+///         3) `field((<SOURCE TYPE>, default))`
+///             - uses the default redefined name (i.e. `<SOURCE
+///               TYPE>Redefined`)
 /// 3) `same_fields`
 ///     - all non-primitive types are converted to the predefined types by
 ///       default, so passing this in will use the same value in the field for
@@ -153,6 +327,27 @@ mod remote;
 ///       ..)`
 ///     - if a type (owned by you) needs to implement `RedefinedConvert`, call
 ///       `self_convert_redefined(<TYPE>)` to implement the trait for itself
+/// 4) `source = <SOURCE TYPE>`
+///     - only applies the other attributes in the same `#[redefined(...)]` when
+///       converting from the given source type
+///     - used when the container has multiple source types
+/// 5) `from = <INDEX>`
+///     - the index of the part of a composed source tuple the field is
+///       converted from (defaults to `0`)
+///     - NOTE: only used when the container attribute is a tuple of source
+///       types (i.e. `#[redefined((A, B))]`)
+/// 6) `from_source = ".."` / `to_source = ".."`
+///     - custom conversions for the field, used as is instead of calling
+///       `RedefinedConvert` (i.e. for callbacks or trait objects)
+///     - NOTE: the source type is a variable called `src` and the target type
+///       is `self`
+///     - field types that can't be redefined (fn pointers, trait objects, `impl
+///       Trait`, raw pointers, macros, ..) are kept unchanged in the new type
+///
+/// On the fields of an enum's variants, `func`, `from_source`, `to_source` and
+/// `source` are supported and `src` is the value of the field in both
+/// directions (i.e. `Value(#[redefined(func = "src * 10")] u64)`), attributes
+/// can't be put on the variants themselves
 ///
 /// #Examples:
 ///
@@ -183,6 +378,38 @@ mod remote;
 /// ```
 ///
 ///
+/// `source = ..`
+/// ```ignore
+///     #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+///     #[redefined(v1::Compression, v2::Compression)]
+///     pub struct Compression {
+///         pub level: u32,
+///         #[redefined(func = "src.name.to_uppercase()", source = v2::Compression)]
+///         pub name:  String,
+///     }
+/// ```
+///
+/// `from = ..`
+/// ```ignore
+///     #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+///     #[redefined((PoolConfig, PoolState))]
+///     pub struct PoolView {
+///         pub fee:     u32,
+///         #[redefined(from = 1)]
+///         pub reserve: u128,
+///     }
+/// ```
+/// `from_source = '..'` + `to_source = '..'`
+/// ```ignore
+///     #[derive(Redefined)]
+///     #[redefined(CallbackStruct)]
+///     pub struct CallbackStructA {
+///         pub val:      u64,
+///         #[redefined(from_source = "src.callback", to_source = "self.callback")]
+///         pub callback: Box<dyn Fn(u64) -> u64 + Send>,
+///     }
+/// ```
+///
 ///
 ///
 /// /// `same_fields`
@@ -196,10 +423,7 @@ mod remote;
 ///     }
 ///     // the new type will have field:
 ///     pub inner_a: Vec<(GenericLifetimeStructA<'a, 'b>, BasicStructA)>,
-///
-///    
 /// ```
-
 #[proc_macro_derive(Redefined, attributes(redefined, redefined_attr))]
 pub fn derive_redefined(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
@@ -215,18 +439,87 @@ pub fn derive_redefined(input: TokenStream) -> TokenStream {
 ///     
 /// 1. *(Optional)* `#[derives(...)]` for derives on new type
 /// 2. *(Optional)* other container attributes (i.e. `#[...]`) for on new type
-/// 3. Identifiers of the remote types (comma seperated surrounded by
-/// brackets: `[A, B, ..]`)     
-/// 4. the crate of the remote type as it appears in
-/// Cargo.toml
+///     - `#[redefined_attr(map_types(...))]`, `#[redefined_attr(vis = "..",
+///       field_vis = "..")]` apply to the new type (see the derive macro's
+///       container attributes), i.e. to expose the private fields of a remote
+///       type
+///     - `#[redefined_attr(concrete)]` mirrors type aliases as concrete types
+///       (see below)
+///     - `#[redefined_attr(recursive)]` also mirrors the remote types used in
+///       the fields of the remote types, and the ones used in their fields,
+///       once each. Primitives, std types, generic parameters and types that
+///       aren't in a package in `Cargo.lock` are left alone. The nesting is
+///       limited by `max_depth = ..` (default `8`), types in `exclude(A, B)`
+///       aren't mirrored (i.e. when they are mirrored by another call), by
+///       their identifier or the end of their path (i.e. `exclude(a::Error)`).
+///       Deeper types, types that can't be named outside of their crate, types
+///       from packages that aren't dependencies of the crate being compiled and
+///       types with the same identifier in different modules (which would have
+///       new types with the same name) are errors
+///     - `#[redefined_attr(search(examples, tests, benches))]` also searches
+///       the files of the package's examples, tests or benches if the type
+///       isn't in it's library. By default only the library is searched (all of
+///       the package's other sources if it has none), never the build script or
+///       the other packages of it's repository
+///     - `concrete`, `recursive`, `max_depth`, `exclude` and `search` must be
+///       in their own `#[redefined_attr(..)]`
+/// 3. Identifiers of the remote types (comma seperated surrounded by brackets:
+///    `[A, B, ..]`)
+///     - `A as B` names the new type `B` instead of `ARedefined`. Types
+///       expanded after it in the same crate use the name for fields of the
+///       remote type, by it's full path or the end of it. Mirroring the remote
+///       type again replaces the name for the types expanded after that
+///     - paths (i.e. `ruint::algorithms::Foo` or `algorithms::Foo`) select the
+///       type by the modules it's defined in, following the `mod` declarations
+///       of the crate's lib target. A path starting with the crate's name is
+///       matched from the crate's root, otherwise it matches the end of the
+///       type's module path. The path is also used as the source type, so it
+///       must be nameable where the macro is called
+///     - types re-exported with `pub use` (i.e. `alloy::primitives::Address`)
+///       are found by following the re-exports, including into other crates in
+///       `Cargo.lock`, to the type's definition
+///     - type aliases (i.e. `ruint::aliases::U128`) are followed to the type
+///       they alias. By default the aliased type is mirrored and the alias
+///       becomes an alias of it's new type (`type U128Redefined =
+///       UintRedefined<128, 2>;`), so the aliased type must be nameable from
+///       it's crate's path. With `#[redefined_attr(concrete)]` a new type with
+///       the alias's name and the generic arguments substituted is generated
+///       instead. Aliases generated by macros are not found
+///     - items, fields and variants disabled by `#[cfg(..)]` are skipped and
+///       `#[cfg_attr(..)]` is expanded, with the features cargo enables for the
+///       remote package (from `cargo metadata`, or it's default features) and
+///       the options of the target from `rustc --print cfg` (i.e.
+///       `target_feature`). `test`, `miri` and `--cfg` options are only set for
+///       the crate being compiled, and the options set by the package's build
+///       script (`cargo:rustc-cfg=..`) aren't known, so they are never set for
+///       the remote package
+/// 4. the crate of the remote type as it appears in Cargo.toml
 ///     - **NOTE**: If the type is part of a workspace, make sure the package
 ///       referenced is the workspace package
+///     - the package is located with `cargo metadata` (or `Cargo.lock` if it
+///       can't resolve offline), from a registry, git (any host, checked out at
+///       the locked commit), a path dependency or a workspace member. A renamed
+///       dependency (`primitives = { package = "alloy-primitives" }`) can be
+///       referenced by it's name
+///     - registry packages are read from the source replacing the registry in
+///       `.cargo/config.toml` (a `directory`, i.e. from `cargo vendor`, or a
+///       `local-registry`), otherwise from their unpacked sources in the cargo
+///       home or their `.crate` archive in it's cache, which is unpacked into
+///       `target/redefined/crates`. Nothing is downloaded
+///     - `"ruint@1.12"` selects the locked version matching the requirement,
+///       it's needed when several versions are locked and the crate calling the
+///       macro doesn't depend on one of them
+/// 5. *(Optional)* all of the above wrapped in `mod <MODULE> { .. }` (or `pub
+///    mod ..`) to generate the new types in a module, where they reference each
+///    other
 ///
 /// ## Macro Output
 /// - A new type with the same fields as the old type
 /// - It's name is the same expect with a concatenated `Redefined`
+/// - The attributes of the remote type and it's fields are dropped, except
+///   docs, `#[repr(..)]` and `#[default]`
 /// - For nested types, you also need to call use the macro on each of the
-///   nested types
+///   nested types, or use `#[redefined_attr(recursive)]`
 /// - The new type automatically derives `From<OLD TYPE>` and `Into<Old Type>`
 ///
 ///
@@ -242,8 +535,14 @@ pub fn derive_redefined(input: TokenStream) -> TokenStream {
 ///
 ///    redefined_remote!([Uint] : "ruint");
 ///    redefined_remote!(#[derive(Clone)] [Uint] : "ruint");
+///    redefined_remote!([Uint as U256Mirror] : "ruint");
+///    redefined_remote!([ruint::Uint as U256PathMirror] : "ruint");
+///    redefined_remote!(pub mod mirror { [Uint, Signed] : "ruint" });
+///    redefined_remote!([Uint] : "ruint@1.12");
+///    redefined_remote!([ruint::aliases::U128] : "ruint");
+///    redefined_remote!(#[redefined_attr(concrete)] [ruint::aliases::U64] : "ruint");
+///    redefined_remote!(#[redefined_attr(recursive, max_depth = 2)] [ruint::Bits] : "ruint");
 /// ```
-
 #[cfg(feature = "remote")]
 #[proc_macro]
 pub fn redefined_remote(input: TokenStream) -> TokenStream {
@@ -1,9 +1,9 @@
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{self, Attribute, DataEnum, Fields, Generics, Ident, Variant, Visibility};
+use syn::{self, DataEnum, Fields, Generics, Ident, Variant, Visibility};
 
-use super::parse_attributes;
-use crate::new_types::r#struct::parse_field;
+use super::NewTypeAttributes;
+use crate::{attributes::type_map::TypeMap, new_types::r#struct::parse_field};
 
 pub fn parse_new_enum(
     data_enum: &DataEnum,
@@ -11,33 +11,39 @@ pub fn parse_new_enum(
     new_enum_name: &Ident,
     generics: &Generics,
     visibility: &Visibility,
-    attributes: &[Attribute],
+    new_type_attrs: NewTypeAttributes,
     generics_skip_remote: &[Ident],
 ) -> syn::Result<TokenStream> {
+    let NewTypeAttributes { derive_attrs, container_attrs, new_attrs, type_map, vis, field_vis, crate_attr, .. } = new_type_attrs;
+    let visibility = vis.as_ref().unwrap_or(visibility);
+    // fields of enum variants can't have a visibility
+    if field_vis.is_some() {
+        return Err(syn::Error::new_spanned(enum_name, "The `field_vis` attribute can't be used on an enum"));
+    }
+
     let enum_fields = data_enum
         .variants
         .iter()
-        .map(|variant| parse_enum_variant(variant, generics_skip_remote))
+        .map(|variant| parse_enum_variant(variant, &type_map, generics_skip_remote))
         .collect::<syn::Result<Vec<_>>>()?;
 
-    let (derive_attrs, container_attrs, new_attrs) = parse_attributes(attributes, enum_name.span())?;
+    let where_clause = &generics.where_clause;
 
     let tokens = quote! {
         #[derive(#(#derive_attrs),*)]
         #[redefined(#enum_name)]
+        #crate_attr
         #(#container_attrs)*
         #(#new_attrs)*
-        #visibility enum #new_enum_name #generics {
+        #visibility enum #new_enum_name #generics #where_clause {
             #(#enum_fields),*
         }
     };
 
-    //panic!("TOKENS: {:?}", tokens.to_string());
-
     Ok(tokens)
 }
 
-fn parse_enum_variant(variant: &Variant, generics_skip_remote: &[Ident]) -> syn::Result<TokenStream> {
+fn parse_enum_variant(variant: &Variant, type_map: &TypeMap, generics_skip_remote: &[Ident]) -> syn::Result<TokenStream> {
     let discriminant = &variant.discriminant;
     let ident = &variant.ident;
     let mut copied_field_attrs = Vec::new();
@@ -47,7 +53,7 @@ fn parse_enum_variant(variant: &Variant, generics_skip_remote: &[Ident]) -> syn:
             let f = fields
                 .named
                 .iter()
-                .map(|f| parse_field(f, generics_skip_remote))
+                .map(|f| parse_field(f, type_map, None, generics_skip_remote))
                 .collect::<Result<Vec<_>, _>>()?;
             quote! { {#(#f),* }}
         }
@@ -55,7 +61,7 @@ fn parse_enum_variant(variant: &Variant, generics_skip_remote: &[Ident]) -> syn:
             let f = fields
                 .unnamed
                 .iter()
-                .map(|f| parse_field(f, generics_skip_remote))
+                .map(|f| parse_field(f, type_map, None, generics_skip_remote))
                 .collect::<Result<Vec<_>, _>>()?;
             quote! { (#(#f),*)}
         }
@@ -63,21 +69,17 @@ fn parse_enum_variant(variant: &Variant, generics_skip_remote: &[Ident]) -> syn:
     };
 
     for attr in &variant.attrs {
-        if !attr.path().is_ident("redefined") {
-            copied_field_attrs.push(attr)
+        if attr.path().is_ident("redefined") {
+            return Err(syn::Error::new_spanned(attr, "`#[redefined(..)]` can't be used on an enum variant, put it on the variant's fields"));
         }
+        copied_field_attrs.push(attr)
     }
 
-    let tokens = if let Some((eq, expr)) = discriminant {
-        quote! {
-            #(#copied_field_attrs)*
-            #ident #eq #expr,
-        }
-    } else {
-        quote! {
-            #(#copied_field_attrs)*
-            #ident #fields
-        }
+    let discriminant = discriminant.as_ref().map(|(eq, expr)| quote!(#eq #expr));
+
+    let tokens = quote! {
+        #(#copied_field_attrs)*
+        #ident #fields #discriminant
     };
 
     Ok(tokens)
@@ -3,83 +3,232 @@ pub mod r#struct;
 
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
-use syn::{parse::Parse, Attribute, Data, DeriveInput};
+use syn::{parse::Parse, parse_quote, Attribute, Data, DeriveInput, Expr, Path, Visibility};
 
 use self::{r#enum::parse_new_enum, r#struct::parse_new_struct};
-use crate::{attributes::ContainerAttributes, new_types::r#struct::parse_type_to_redefined, outer::OuterContainer};
+use crate::{
+    attributes::{
+        symbol::{CRATE, FIELD_VIS, MODULE, NAME, VIS},
+        type_map::TypeMap,
+        ContainerAttributes,
+    },
+    config::Config,
+    new_types::r#struct::parse_type_to_redefined,
+    outer::{explicit_crate_path, OuterContainer},
+    registry::{new_type_name, record_new_type},
+};
 
 pub fn parse_type_without_source(outer: OuterContainer, input: &DeriveInput, is_remote: bool) -> syn::Result<TokenStream> {
-    let input_data = &input.data;
+    let config = Config::load()?;
+    // the default derives and attributes of remote types are added by
+    // `redefined_remote!`
+    let mut attrs = input.attrs.clone();
+    if !is_remote {
+        attrs.extend(config.new_type_attrs()?);
+    }
+
+    let mut new_type_attrs = parse_attributes(&attrs, outer.target_type.span())?;
+    let mut input_data = input.data.clone();
+    let mut input_vis = input.vis.clone();
+    let module_vis = new_type_attrs
+        .vis
+        .clone()
+        .unwrap_or_else(|| input.vis.clone());
+    // types generated in a module keep their visibility from the calling module
+    if new_type_attrs.module.is_some() {
+        input_vis = nested_visibility(&input_vis);
+        new_type_attrs.vis = new_type_attrs.vis.as_ref().map(nested_visibility);
+        new_type_attrs.field_vis = new_type_attrs.field_vis.as_ref().map(nested_visibility);
+        if let Data::Struct(data_struct) = &mut input_data {
+            data_struct
+                .fields
+                .iter_mut()
+                .for_each(|field| field.vis = nested_visibility(&field.vis));
+        }
+    }
 
     let mut input_generics = input.generics.clone();
     if is_remote {
-        input_generics
-            .params
-            .iter_mut()
-            .for_each(|param| match param {
-                syn::GenericParam::Type(path) => {
-                    if let Some(default_val) = path.default.as_mut() {
-                        *default_val = parse_type_to_redefined(default_val, &Default::default(), Default::default())
-                    }
-
-                    // if path.default.is_some() {
-                    //     path.default = None;
-                    //     path.eq_token = None;
-                    // }
+        for param in input_generics.params.iter_mut() {
+            if let syn::GenericParam::Type(path) = param {
+                if let Some(default_val) = path.default.as_mut() {
+                    *default_val = parse_type_to_redefined(default_val, &new_type_attrs.type_map, Default::default())?
                 }
-                _ => (),
-            });
-        //panic!("DEF: \n{:?}", input_generics.to_token_stream().to_string());
+            }
+        }
     }
-    let source_type = Ident::new(&format!("{}Redefined", outer.target_type), outer.target_type.span());
+    let name = parse_new_type_name(&attrs)?;
+    // remote types are recorded by their path by `redefined_remote!`
+    if !is_remote {
+        record_new_type(&outer.target_type, name.as_ref(), new_type_attrs.module.as_ref());
+    }
+    let source_type = match name {
+        Some(name) => name,
+        None => new_type_name(&outer.target_type)?,
+    };
 
     let generic_vec = input_generics
         .type_params()
         .map(|p| p.ident.clone())
+        .chain(input_generics.const_params().map(|p| p.ident.clone()))
         .collect::<Vec<_>>();
 
+    let module = new_type_attrs.module.clone();
     let new_type_tokens = match &input_data {
         Data::Struct(data_struct) => {
-            parse_new_struct(data_struct, &outer.target_type, &source_type, &input_generics, &input.vis, &input.attrs, &generic_vec)
+            parse_new_struct(data_struct, &outer.target_type, &source_type, &input_generics, &input_vis, new_type_attrs, &generic_vec)
+        }
+        Data::Enum(data_enum) => {
+            parse_new_enum(data_enum, &outer.target_type, &source_type, &input_generics, &input_vis, new_type_attrs, &generic_vec)
         }
-        Data::Enum(data_enum) => parse_new_enum(data_enum, &outer.target_type, &source_type, &input_generics, &input.vis, &input.attrs, &generic_vec),
         _ => return Err(syn::Error::new_spanned(source_type, "Expected an enum or struct")),
     }?;
 
-    // panic!("NEW TYPE: \n{}", new_type_tokens.to_string());
+    match module {
+        Some(module) => Ok(module_tokens(&module_vis, &module, new_type_tokens)),
+        None => Ok(quote!( #new_type_tokens )),
+    }
+}
 
-    Ok(quote!( #new_type_tokens ))
+/// wraps generated types in a module, which sees the items of the calling
+/// module
+pub fn module_tokens(vis: &Visibility, module: &Ident, tokens: TokenStream) -> TokenStream {
+    quote! {
+        #vis mod #module {
+            #[allow(unused_imports)]
+            use super::*;
+
+            #tokens
+        }
+    }
+}
+
+/// the visibility inside a generated module with the same meaning as the
+/// visibility in the calling module (i.e. private -> `pub(super)`)
+pub fn nested_visibility(vis: &Visibility) -> Visibility {
+    match vis {
+        Visibility::Inherited => parse_quote!(pub(super)),
+        Visibility::Restricted(restricted) => {
+            let path = &restricted.path;
+            if path.is_ident("self") {
+                parse_quote!(pub(super))
+            } else if path
+                .segments
+                .first()
+                .is_some_and(|seg| seg.ident == "super")
+            {
+                parse_quote!(pub(in super::#path))
+            } else {
+                vis.clone()
+            }
+        }
+        Visibility::Public(_) => vis.clone(),
+    }
 }
 
-pub fn parse_attributes(attrs: &[Attribute], span: Span) -> syn::Result<(Vec<Ident>, Vec<Attribute>, Vec<TokenStream>)> {
-    let mut derive_attrs = vec![Ident::new("Redefined", span.clone())];
+/// the name of the new type given by `#[redefined_attr(name = "..")]`
+fn parse_new_type_name(attrs: &[Attribute]) -> syn::Result<Option<Ident>> {
+    for attr in attrs {
+        if attr.path().is_ident("redefined_attr") {
+            let parsed_attrs = attr.parse_args_with(ContainerAttributes::parse)?.0;
+            if let Some(name_attr) = NAME.find_type_attr(&parsed_attrs) {
+                let name = match &name_attr.nv_tokens {
+                    Some(Expr::Path(path)) => path.path.get_ident().cloned(),
+                    _ => None,
+                }
+                .ok_or_else(|| syn::Error::new_spanned(&name_attr.nv_tokens, "Expected an identifier for the name of the new type"))?;
+
+                return Ok(Some(name));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// the attributes of a new type
+pub struct NewTypeAttributes {
+    /// derives of the new type, including `Redefined`
+    pub derive_attrs:    Vec<Path>,
+    /// other attributes copied to the new type
+    pub container_attrs: Vec<Attribute>,
+    /// attributes from `other(..)`
+    pub new_attrs:       Vec<TokenStream>,
+    pub type_map:        TypeMap,
+    /// the visibility of the new type, from `vis = ".."`
+    pub vis:             Option<Visibility>,
+    /// the visibility of every field of the new type, from `field_vis = ".."`
+    pub field_vis:       Option<Visibility>,
+    /// the module the new type is generated in, from `module = ".."`
+    pub module:          Option<Ident>,
+    /// `crate = ".."`, forwarded to the new type
+    pub crate_attr:      Option<Attribute>,
+}
+
+pub fn parse_attributes(attrs: &[Attribute], span: Span) -> syn::Result<NewTypeAttributes> {
+    let mut derives: Vec<Ident> = Vec::new();
     let mut container_attrs = Vec::new();
     let mut new_attrs = Vec::new();
+    let mut type_map = TypeMap::default();
+    let (mut vis, mut field_vis, mut module) = (None, None, None);
+    let mut crate_attrs = Vec::new();
 
     for attr in attrs {
         if attr.path().is_ident("redefined_attr") {
-            let redef_attrs = attr
-                .parse_args_with(ContainerAttributes::parse)?
-                .0
-                .into_iter()
-                .filter_map(|a| a.list_idents)
+            let parsed_attrs = attr.parse_args_with(ContainerAttributes::parse)?.0;
+            type_map = type_map.with_fallback(&TypeMap::from_container_attrs(&parsed_attrs));
+
+            if let Some(vis_attr) = VIS.find_type_attr(&parsed_attrs) {
+                vis = Some(vis_attr.parse_nv_str()?);
+            }
+            if let Some(field_vis_attr) = FIELD_VIS.find_type_attr(&parsed_attrs) {
+                field_vis = Some(field_vis_attr.parse_nv_str()?);
+            }
+            if let Some(module_attr) = MODULE.find_type_attr(&parsed_attrs) {
+                module = Some(module_attr.parse_nv_str()?);
+            }
+            crate_attrs.extend(CRATE.find_type_attr(&parsed_attrs));
+
+            let redef_attrs = parsed_attrs
+                .iter()
+                .filter_map(|a| a.list_idents.clone())
                 .flatten()
                 .collect::<Vec<_>>();
 
             new_attrs.extend(
-                attr.parse_args_with(ContainerAttributes::parse)?
-                    .0
+                parsed_attrs
                     .into_iter()
                     .filter_map(|a| a.list_other_attrs)
                     .flatten()
                     .collect::<Vec<_>>(),
             );
 
-            derive_attrs.extend(redef_attrs);
+            for derive in redef_attrs {
+                if derive != "Redefined" && !derives.contains(&derive) {
+                    derives.push(derive);
+                }
+            }
         } else {
             container_attrs.push(attr.clone())
         }
     }
 
-    Ok((derive_attrs, container_attrs, new_attrs))
+    // the global type mappings have the lowest precedence
+    let type_map = type_map.with_fallback(&Config::load()?.type_map()?);
+
+    // `Redefined` is derived through the crate path if it's given
+    let redefined_derive = match explicit_crate_path(&crate_attrs)? {
+        Some(crate_path) => parse_quote!(#crate_path::Redefined),
+        None => Path::from(Ident::new("Redefined", span)),
+    };
+    let derive_attrs = std::iter::once(redefined_derive)
+        .chain(derives.into_iter().map(Path::from))
+        .collect();
+
+    let crate_attr = crate_attrs.last().and_then(|crate_attr| {
+        let crate_path = crate_attr.nv_tokens.as_ref()?;
+        Some(parse_quote!(#[redefined_attr(crate = #crate_path)]))
+    });
+
+    Ok(NewTypeAttributes { derive_attrs, container_attrs, new_attrs, type_map, vis, field_vis, module, crate_attr })
 }
@@ -1,15 +1,16 @@
-use std::collections::HashMap;
-
 use proc_macro2::TokenStream;
 use quote::quote;
-use syn::{self, parse::Parse, spanned::Spanned, Attribute, DataStruct, Field, Fields, Generics, Ident, Type, Visibility};
-
-use super::parse_attributes;
-use crate::attributes::{
-    primitives::is_simple_primitive,
-    symbol::{FIELD_FN, USE_FIELD, USE_SAME_FIELD, USE_SAME_FIELDS},
-    type_attr::TypeAttribute,
-    ContainerAttributes,
+use syn::{self, parse::Parse, DataStruct, Field, Fields, GenericArgument, Generics, Ident, Path, PathArguments, ReturnType, Type, Visibility};
+
+use super::NewTypeAttributes;
+use crate::{
+    attributes::{
+        primitives::{is_simple_primitive, is_std_type},
+        symbol::{FIELD_FN, FROM_SOURCE_FN, TO_SOURCE_FN, USE_FIELD, USE_SAME_FIELD, USE_SAME_FIELDS},
+        type_map::TypeMap,
+        ContainerAttributes,
+    },
+    registry::redefine_path_segment,
 };
 
 pub fn parse_new_struct(
@@ -18,7 +19,7 @@ pub fn parse_new_struct(
     new_struct_name: &Ident,
     generics: &Generics,
     visibility: &Visibility,
-    attributes: &[Attribute],
+    new_type_attrs: NewTypeAttributes,
     generics_skip_remote: &[Ident],
 ) -> syn::Result<TokenStream> {
     let fields = match &data_struct.fields {
@@ -27,30 +28,33 @@ pub fn parse_new_struct(
         _ => return Err(syn::Error::new_spanned(&data_struct.fields, "Expected a struct with named/unnamed fields")),
     };
 
-    let (derive_attrs, container_attrs, new_attrs) = parse_attributes(attributes, struct_name.span())?;
-
-    // panic!("NEW ATTR: \n{:?}", new_attrs);
+    let NewTypeAttributes { derive_attrs, container_attrs, new_attrs, type_map, vis, field_vis, crate_attr, .. } = new_type_attrs;
+    let visibility = vis.as_ref().unwrap_or(visibility);
 
     let struct_fields = fields
         .iter()
-        .map(|field| parse_field(field, generics_skip_remote))
+        .map(|field| parse_field(field, &type_map, field_vis.as_ref(), generics_skip_remote))
         .collect::<syn::Result<Vec<_>>>()?;
 
+    let where_clause = &generics.where_clause;
+
     let tokens = if let Some(semi_token) = data_struct.semi_token {
         quote! {
             #[derive(#(#derive_attrs),*)]
             #[redefined(#struct_name)]
+            #crate_attr
             #(#container_attrs)*
             #(#new_attrs)*
-            #visibility struct #new_struct_name #generics (#(#struct_fields),*)#semi_token
+            #visibility struct #new_struct_name #generics (#(#struct_fields),*) #where_clause #semi_token
         }
     } else {
         quote! {
             #[derive(#(#derive_attrs),*)]
             #[redefined(#struct_name)]
+            #crate_attr
             #(#container_attrs)*
             #(#new_attrs)*
-            #visibility struct #new_struct_name #generics {
+            #visibility struct #new_struct_name #generics #where_clause {
                 #(#struct_fields),*
             }
         }
@@ -59,11 +63,18 @@ pub fn parse_new_struct(
     Ok(tokens)
 }
 
-pub fn parse_field(field: &Field, generics_skip_remote: &[Ident]) -> syn::Result<TokenStream> {
+/// parses a field of the new type, `field_vis` overrides the field's
+/// visibility
+pub fn parse_field(
+    field: &Field,
+    container_type_map: &TypeMap,
+    field_vis: Option<&Visibility>,
+    generics_skip_remote: &[Ident],
+) -> syn::Result<TokenStream> {
     let ident = &field.ident;
     let _mutability = &field.mutability;
     let colon_token = field.colon_token;
-    let vis = &field.vis;
+    let vis = field_vis.unwrap_or(&field.vis);
     let mut ty = field.ty.clone();
     let mut copied_field_attrs = Vec::new();
     let mut field_attrs = Vec::new();
@@ -71,14 +82,11 @@ pub fn parse_field(field: &Field, generics_skip_remote: &[Ident]) -> syn::Result
     for attr in &field.attrs {
         if attr.path().is_ident("redefined") {
             let redefined_attr = attr.parse_args_with(ContainerAttributes::parse)?.0;
-            let d = TypeAttribute {
-                symbol:            FIELD_FN,
-                nv_tokens:         None,
-                list_idents:       None,
-                list_tuple_idents: None,
-                list_other_attrs:  None,
-            };
-            if redefined_attr.contains(&d) {
+            // conversion attributes are used by the new type's derive
+            if [FIELD_FN, FROM_SOURCE_FN, TO_SOURCE_FN]
+                .iter()
+                .any(|symbol| symbol.contained_in(&redefined_attr))
+            {
                 copied_field_attrs.push(attr);
             }
 
@@ -87,22 +95,22 @@ pub fn parse_field(field: &Field, generics_skip_remote: &[Ident]) -> syn::Result
             copied_field_attrs.push(attr)
         }
     }
-    let mut attr_types = HashMap::new();
+    let mut attr_types = TypeMap::default();
     if let Some(attr) = field_attrs.iter().find(|s| s.symbol == USE_FIELD).cloned() {
-        attr_types = attr
-            .list_tuple_idents
-            .unwrap()
-            .into_iter()
-            .map(|(source, target)| (source, target))
-            .collect();
+        attr_types = TypeMap::new(
+            attr.list_type_pairs
+                .ok_or_else(|| syn::Error::new_spanned(&field.ty, "Expected `field((<SOURCE TYPE>, <TARGET TYPE>), ..)`"))?,
+        );
     }
+    // the field's mappings take precedence over the container's
+    let attr_types = attr_types.with_fallback(container_type_map);
 
     if field_attrs
         .iter()
         .find(|s| s.symbol == USE_SAME_FIELDS)
         .is_none()
     {
-        ty = parse_type_to_redefined(&ty, &attr_types, generics_skip_remote);
+        ty = parse_type_to_redefined(&ty, &attr_types, generics_skip_remote)?;
     }
 
     let tokens = quote! {
@@ -113,83 +121,126 @@ pub fn parse_field(field: &Field, generics_skip_remote: &[Ident]) -> syn::Result
     Ok(tokens)
 }
 
-pub fn parse_type_to_redefined(src_type: &Type, new_type_names: &HashMap<Ident, Ident>, generics_skip_remote: &[Ident]) -> Type {
-    match src_type {
+pub fn parse_type_to_redefined(src_type: &Type, new_type_names: &TypeMap, generics_skip_remote: &[Ident]) -> syn::Result<Type> {
+    let new_type = match src_type {
         Type::Array(a) => {
             let mut array = a.clone();
-            let new_type = parse_type_to_redefined(&a.elem, new_type_names, generics_skip_remote);
+            let new_type = parse_type_to_redefined(&a.elem, new_type_names, generics_skip_remote)?;
             array.elem = Box::new(new_type);
             Type::Array(array)
         }
         Type::Reference(r) => {
             let mut refer = r.clone();
-            let new_type = parse_type_to_redefined(&r.elem, new_type_names, generics_skip_remote);
+            let new_type = parse_type_to_redefined(&r.elem, new_type_names, generics_skip_remote)?;
             refer.elem = Box::new(new_type);
             Type::Reference(refer)
         }
         Type::Slice(s) => {
             let mut slice = s.clone();
-            let new_type = parse_type_to_redefined(&s.elem, new_type_names, generics_skip_remote);
+            let new_type = parse_type_to_redefined(&s.elem, new_type_names, generics_skip_remote)?;
             slice.elem = Box::new(new_type);
             Type::Slice(slice)
         }
         Type::Path(p) => {
             let mut path = p.clone();
-            //panic!("TOOOO\n {:?}\n", p.path.get_ident());
-            path.path.segments.iter_mut().for_each(|seg| {
-                //panic!("TOOOO\n {}\n{}", seg.ident, Primitive::is_primitive(&seg.ident));
-                //panic!("TOOOO\n {}\n{:?}\n{}", seg.ident, new_type_names.pop_front(),
-                // new_type_names.len(), seg.arguments);
-
-                if let Some(target) = new_type_names.get(&seg.ident) {
-                    if target != USE_SAME_FIELD {
-                        seg.ident = Ident::new(&format!("{}Redefined", seg.ident), seg.span())
-                    }
-                } else {
-                    match &mut seg.arguments {
-                        syn::PathArguments::None => {
-                            //panic!("TOOOO\n {}\n{}", seg.ident, Primitive::is_primitive(&seg.ident));
-
-                            if !is_simple_primitive(&seg.ident.to_string()) && !generics_skip_remote.contains(&seg.ident) {
-                                seg.ident = Ident::new(&format!("{}Redefined", seg.ident), seg.span())
-                            }
-                        }
-
-                        syn::PathArguments::AngleBracketed(a) => a.args.iter_mut().for_each(|arg| match arg {
-                            syn::GenericArgument::Type(t) => *t = parse_type_to_redefined(&t, new_type_names, generics_skip_remote),
-                            _ => (),
-                        }),
-                        syn::PathArguments::Parenthesized(p) => p
-                            .inputs
-                            .iter_mut()
-                            .for_each(|t| *t = parse_type_to_redefined(&t, new_type_names, generics_skip_remote)),
-                    }
-                }
-            });
+            // qualified paths (i.e. `<T as Trait>::Assoc`) are kept unchanged
+            if path.qself.is_none() {
+                parse_path_to_redefined(&mut path.path, new_type_names, generics_skip_remote)?;
+            }
 
             Type::Path(path)
         }
         Type::Tuple(t) => {
             let mut tuple = t.clone();
-            tuple
-                .elems
-                .iter_mut()
-                .for_each(|e| *e = parse_type_to_redefined(&e, new_type_names, generics_skip_remote));
+            for e in tuple.elems.iter_mut() {
+                *e = parse_type_to_redefined(e, new_type_names, generics_skip_remote)?;
+            }
 
-            //panic!("TUPLE: {:?}\nMAP: {:?}", tuple.to_token_stream().to_string(),
-            // &new_type_names);
             Type::Tuple(tuple)
         }
-        Type::BareFn(_) => panic!("FIELD IS OF TYPE: BareFn"),
-        Type::Group(_) => panic!("FIELD IS OF TYPE: Group"),
-        Type::ImplTrait(_) => panic!("FIELD IS OF TYPE: ImplTrait"),
-        Type::Infer(_) => panic!("FIELD IS OF TYPE: Infer"),
-        Type::Macro(_) => panic!("FIELD IS OF TYPE: Macro"),
-        Type::Never(_) => panic!("FIELD IS OF TYPE: Never"),
-        Type::Paren(_) => panic!("FIELD IS OF TYPE: Paren"),
-        Type::Ptr(_) => panic!("FIELD IS OF TYPE: Ptr"),
-        Type::TraitObject(_) => panic!("FIELD IS OF TYPE: TraitObject"),
-        Type::Verbatim(_) => panic!("FIELD IS OF TYPE: Verbatim"),
-        _ => panic!("FIELD IS OF TYPE: _"),
+        Type::Paren(p) => {
+            let mut paren = p.clone();
+            let new_type = parse_type_to_redefined(&p.elem, new_type_names, generics_skip_remote)?;
+            paren.elem = Box::new(new_type);
+            Type::Paren(paren)
+        }
+        Type::Group(g) => {
+            let mut group = g.clone();
+            let new_type = parse_type_to_redefined(&g.elem, new_type_names, generics_skip_remote)?;
+            group.elem = Box::new(new_type);
+            Type::Group(group)
+        }
+        // fn pointers, trait objects, `impl Trait`, raw pointers, macros, `!`, `_`
+        // and verbatim types are kept unchanged, use the field's `from_source` /
+        // `to_source` attributes for a custom conversion
+        _ => src_type.clone(),
+    };
+
+    Ok(new_type)
+}
+
+/// rewrites the last segment of a path to it's redefined type, module
+/// segments, std types, primitives, generic parameters and associated types
+/// of generic parameters (i.e. `T::Assoc`) are kept unchanged
+fn parse_path_to_redefined(path: &mut Path, new_type_names: &TypeMap, generics_skip_remote: &[Ident]) -> syn::Result<()> {
+    // `(<SOURCE TYPE>, same)` keeps the type, `(<SOURCE TYPE>, default)` uses the
+    // default redefined name, otherwise the path is replaced by the target type
+    // (keeping the generic arguments if neither type has any)
+    if let Some((source, target)) = new_type_names.get(path) {
+        if target.is_ident("default") {
+            redefine_path_segment(path)?
+        } else if target != USE_SAME_FIELD {
+            let mut target = target.clone();
+            let source_has_args = source
+                .segments
+                .last()
+                .is_some_and(|seg| !seg.arguments.is_none());
+            if let (Some(target_seg), Some(seg)) = (target.segments.last_mut(), path.segments.last()) {
+                if !source_has_args && target_seg.arguments.is_none() {
+                    target_seg.arguments = seg.arguments.clone();
+                }
+            }
+            *path = target;
+        }
+        return Ok(());
     }
+
+    if path.segments.len() > 1 {
+        let first = &path.segments[0].ident;
+        if first == "Self" || generics_skip_remote.contains(first) {
+            return Ok(());
+        }
+    }
+
+    let Some(seg) = path.segments.last() else { return Ok(()) };
+
+    let ident = seg.ident.to_string();
+    if !is_simple_primitive(&ident) && !is_std_type(path) && ident != "Self" && !generics_skip_remote.contains(&seg.ident) {
+        redefine_path_segment(path)?
+    }
+
+    let Some(seg) = path.segments.last_mut() else { return Ok(()) };
+
+    match &mut seg.arguments {
+        PathArguments::None => (),
+        PathArguments::AngleBracketed(a) => {
+            for arg in a.args.iter_mut() {
+                match arg {
+                    GenericArgument::Type(t) => *t = parse_type_to_redefined(t, new_type_names, generics_skip_remote)?,
+                    GenericArgument::AssocType(assoc) => assoc.ty = parse_type_to_redefined(&assoc.ty, new_type_names, generics_skip_remote)?,
+                    _ => (),
+                }
+            }
+        }
+        PathArguments::Parenthesized(p) => {
+            for t in p.inputs.iter_mut() {
+                *t = parse_type_to_redefined(t, new_type_names, generics_skip_remote)?
+            }
+            if let ReturnType::Type(_, t) = &mut p.output {
+                **t = parse_type_to_redefined(t, new_type_names, generics_skip_remote)?
+            }
+        }
+    }
+
+    Ok(())
 }
@@ -1,37 +1,59 @@
-use syn::{self, parse::Parse, Attribute, Ident};
+use syn::{self, parenthesized, parse::Parse, parse_quote, token, Attribute, Ident, Path, Token};
 
 #[cfg(feature = "unsafe")]
 use crate::attributes::symbol::TRANSMUTE;
-use crate::attributes::{
-    symbol::{Symbol, FROM_SOURCE_FN, TO_SOURCE_FN},
-    type_attr::TypeAttribute,
-    ContainerAttributes,
+use crate::{
+    attributes::{
+        symbol::{Symbol, CRATE, FROM_SOURCE_FN, TO_SOURCE_FN},
+        type_attr::TypeAttribute,
+        ContainerAttributes,
+    },
+    config::Config,
 };
 
 pub struct OuterContainer {
     pub target_type:     Ident,
-    pub source_type:     Option<Ident>,
+    pub source_types:    Vec<Path>,
+    /// tuples of source types composed into the target (i.e. `#[redefined((A,
+    /// B))]`)
+    pub composed_types:  Vec<Vec<Path>>,
     pub container_attrs: Vec<TypeAttribute>,
+    /// the path of the `redefined` crate in generated code
+    pub crate_path:      Path,
 }
 
 impl OuterContainer {
     pub fn parse(target_type: Ident, attrs: &[Attribute]) -> syn::Result<Self> {
         let mut container_attrs = Vec::new();
-        let mut source_type = None;
+        let mut source_types = Vec::new();
+        let mut composed_types = Vec::new();
+        let mut redefined_attrs = Vec::new();
 
         for attr in attrs.iter() {
             if attr.path().is_ident("redefined_attr") {
-                container_attrs = attr.parse_args_with(ContainerAttributes::parse)?.0;
+                let parsed_attrs = attr.parse_args_with(ContainerAttributes::parse)?.0;
+                redefined_attrs.push((attr, parsed_attrs.iter().map(|c| c.symbol).collect::<Vec<_>>()));
+                container_attrs.extend(parsed_attrs);
             }
 
             if attr.path().is_ident("redefined") {
-                source_type = attr.parse_args_with(SourceType::parse)?.0;
+                let parsed = attr.parse_args_with(SourceTypes::parse)?;
+                source_types.extend(parsed.source_types);
+                composed_types.extend(parsed.composed_types);
             }
         }
 
-        Symbol::illegal_pairings(&container_attrs.iter().map(|c| c.symbol).collect::<Vec<_>>(), source_type.is_some());
+        for (container_attr, symbols) in redefined_attrs {
+            Symbol::illegal_pairings(&symbols, !source_types.is_empty() || !composed_types.is_empty(), container_attr)?;
+        }
+
+        let crate_path = explicit_crate_path(&container_attrs)?.unwrap_or_else(|| parse_quote!(::redefined));
+
+        Ok(Self { target_type, source_types, composed_types, container_attrs, crate_path })
+    }
 
-        Ok(Self { target_type, source_type, container_attrs })
+    pub fn has_source_types(&self) -> bool {
+        !self.source_types.is_empty() || !self.composed_types.is_empty()
     }
 
     pub fn should_parse_fields(&self) -> bool {
@@ -56,12 +78,48 @@ impl OuterContainer {
     }
 }
 
-struct SourceType(Option<Ident>);
+/// the path of the `redefined` crate given by `crate = ".."` or the config
+pub fn explicit_crate_path(container_attrs: &[TypeAttribute]) -> syn::Result<Option<Path>> {
+    match CRATE.find_type_attr(container_attrs) {
+        Some(crate_attr) => crate_attr.parse_nv_str().map(Some),
+        None => Config::load()?.crate_path(),
+    }
+}
+
+/// the source types of `#[redefined(...)]`, each either a bare identifier, a
+/// full path with generic arguments (i.e. `ruint::Uint<256, 4>`) or a tuple of
+/// paths composed into the target (i.e. `(ConfigStruct, StateStruct)`)
+struct SourceTypes {
+    source_types:   Vec<Path>,
+    composed_types: Vec<Vec<Path>>,
+}
 
-impl Parse for SourceType {
+impl Parse for SourceTypes {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let source_type = if input.peek(Ident) { Some(input.parse()?) } else { None };
+        let mut source_types = Vec::new();
+        let mut composed_types = Vec::new();
+
+        while !input.is_empty() {
+            if input.peek(token::Paren) {
+                let content;
+                parenthesized!(content in input);
+
+                composed_types.push(
+                    content
+                        .parse_terminated(Path::parse, Token![,])?
+                        .into_iter()
+                        .collect(),
+                );
+            } else {
+                source_types.push(input.parse()?);
+            }
+
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<Token![,]>()?;
+        }
 
-        Ok(Self(source_type))
+        Ok(Self { source_types, composed_types })
     }
 }
@@ -1,21 +1,28 @@
 use proc_macro2::{Span, TokenStream};
-use quote::quote;
-use syn::{self, parse::Parse, DataEnum, Fields, FieldsNamed, FieldsUnnamed, Ident, Variant};
+use quote::{quote, quote_spanned, ToTokens};
+use syn::{self, parse::Parse, spanned::Spanned, DataEnum, Field, Fields, FieldsNamed, FieldsUnnamed, Ident, Path, Variant};
 
-use crate::attributes::{type_attr::TypeAttribute, ContainerAttributes};
+use crate::attributes::{
+    symbol::{FIELD_FN, FROM_SOURCE_FN, SOURCE, TO_SOURCE_FN},
+    type_attr::TypeAttribute,
+    ContainerAttributes,
+};
 
 pub struct EnumContainer {
     pub fields: Vec<EnumField>,
 }
 
 impl EnumContainer {
-    pub fn parse_new(data_enum: &DataEnum) -> syn::Result<Self> {
+    /// parses the variants of the enum, `all_source_types` are every source
+    /// type of the target, which `source = ..` field attributes must refer to
+    /// unambiguously
+    pub fn parse_new(data_enum: &DataEnum, source_path: &Path, all_source_types: &[Path], crate_path: &Path) -> syn::Result<Self> {
         let enum_fields = data_enum
             .variants
             .iter()
             .map(|variant| {
-                let mut this = EnumField::new(variant.ident.clone(), variant.clone());
-                this.parse_attributes_for_field()?;
+                let mut this = EnumField::new(variant.clone(), crate_path.clone());
+                this.parse_attributes_for_field(source_path, all_source_types)?;
                 Ok(this)
             })
             .collect::<syn::Result<Vec<_>>>()?;
@@ -23,7 +30,7 @@ impl EnumContainer {
         Ok(Self { fields: enum_fields })
     }
 
-    pub fn from_source_tokens(&self, source_type: &Ident, target_type: &Ident) -> syn::Result<TokenStream> {
+    pub fn from_source_tokens(&self, source_type: &Path, target_type: &Ident) -> syn::Result<TokenStream> {
         let from_source_tokens = self
             .fields
             .iter()
@@ -38,7 +45,7 @@ impl EnumContainer {
         Ok(gen)
     }
 
-    pub fn to_source_tokens(&self, source_type: &Ident, target_type: &Ident) -> syn::Result<TokenStream> {
+    pub fn to_source_tokens(&self, source_type: &Path, target_type: &Ident) -> syn::Result<TokenStream> {
         let tokens = self
             .fields
             .iter()
@@ -55,119 +62,162 @@ impl EnumContainer {
 }
 
 pub struct EnumField {
-    pub ident:       Ident,
     pub variant:     Variant,
-    pub field_attrs: Vec<TypeAttribute>,
+    /// the attributes of each of the variant's fields
+    pub field_attrs: Vec<Vec<TypeAttribute>>,
+    /// the path of the `redefined` crate
+    pub crate_path:  Path,
 }
 
 impl EnumField {
-    pub fn new(ident: Ident, variant: Variant) -> Self {
-        Self { ident, variant, field_attrs: Vec::new() }
+    pub fn new(variant: Variant, crate_path: Path) -> Self {
+        Self { variant, field_attrs: Vec::new(), crate_path }
     }
 
-    pub fn parse_attributes_for_field(&mut self) -> syn::Result<()> {
-        let mut attrs = Vec::new();
-        for attr in &self.variant.attrs {
-            if attr.path().is_ident("redefined") {
-                attrs.extend(attr.parse_args_with(ContainerAttributes::parse)?.0);
-            }
+    /// parses the attributes of the variant's fields, skipping those specific
+    /// to a different source type (i.e. `#[redefined(func = "..", source =
+    /// A)]`), the variant itself can't have any
+    pub fn parse_attributes_for_field(&mut self, source_path: &Path, all_source_types: &[Path]) -> syn::Result<()> {
+        if let Some(attr) = self
+            .variant
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("redefined"))
+        {
+            return Err(syn::Error::new_spanned(attr, "`#[redefined(..)]` can't be used on an enum variant, put it on the variant's fields"));
         }
-        self.field_attrs = attrs;
-
-        Ok(())
-    }
 
-    pub fn from_source_tokens(&self, source_type: &Ident, target_type: &Ident) -> syn::Result<TokenStream> {
-        let variant_name = &self.variant.ident;
-        let gen = match &self.variant.fields {
-            Fields::Unit => quote! {
-                #source_type::#variant_name =>  #target_type::#variant_name,
-            },
-            Fields::Named(FieldsNamed { named, .. }) => {
-                let field_names: Vec<_> = named
-                    .into_iter()
-                    .map(|f| f.ident.as_ref().unwrap().clone())
-                    .collect();
-                let cloned_field_names = field_names.clone();
-
-                if !field_names.is_empty() {
-                    let field_mappings = cloned_field_names.into_iter().map(|field_name| {
-                        quote! { #field_name: redefined::RedefinedConvert::from_source(#field_name) }
-                    });
-
-                    quote! {
-                        #source_type::#variant_name { #( #field_names ),* } => #target_type::#variant_name { #( #field_mappings ),* },
+        let mut fields_attrs = Vec::new();
+        for field in &self.variant.fields {
+            let mut attrs = Vec::new();
+            for attr in field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("redefined"))
+            {
+                let field_attrs = attr.parse_args_with(ContainerAttributes::parse)?.0;
+                if let Some(source) = SOURCE.find_type_attr(&field_attrs) {
+                    if !source.matches_source(source_path, all_source_types)? {
+                        continue;
                     }
-                } else {
-                    TokenStream::new()
                 }
+
+                if let Some(unsupported) = field_attrs
+                    .iter()
+                    .find(|a| ![FIELD_FN, FROM_SOURCE_FN, TO_SOURCE_FN, SOURCE].contains(&a.symbol))
+                {
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        format!("The `{}` attribute can't be used on a field of an enum variant with a source type", unsupported.symbol),
+                    ));
+                }
+                if FIELD_FN.contained_in(&field_attrs) && FROM_SOURCE_FN.contained_in(&field_attrs) {
+                    return Err(syn::Error::new_spanned(attr, "Cannot have both `func` and `from_source` attributes on a field"));
+                }
+
+                attrs.extend(field_attrs.into_iter().filter(|a| a.symbol != SOURCE));
             }
-            Fields::Unnamed(FieldsUnnamed { unnamed, .. }) => {
-                let field_vars: Vec<_> = (0..unnamed.len())
-                    .map(|i| syn::Ident::new(&format!("x{}", i), Span::call_site()))
-                    .collect();
+            fields_attrs.push(attrs);
+        }
+        self.field_attrs = fields_attrs;
 
-                let destructuring = quote! { (#(#field_vars),*) };
+        Ok(())
+    }
 
-                let construction = quote! {
-                    #target_type::#variant_name(#(redefined::RedefinedConvert::from_source(#field_vars)),*)
-                };
+    pub fn from_source_tokens(&self, source_type: &Path, target_type: &Ident) -> syn::Result<TokenStream> {
+        Ok(self.variant_arm(source_type, target_type, "from_source"))
+    }
 
-                let unamed_gen = quote! {
-                    #source_type::#variant_name #destructuring => #construction,
-                };
+    pub fn to_source_tokens(&self, source_type: &Path, target_type: &Ident) -> syn::Result<TokenStream> {
+        Ok(self.variant_arm(target_type, source_type, "to_source"))
+    }
 
-                unamed_gen
+    /// converts the value of a field, with it's `func = ".."`, `from_source =
+    /// ".."` or `to_source = ".."` attribute if it has one, where `src` is the
+    /// value of the field
+    fn convert_field(&self, field_idx: usize, field: &Field, value: &Ident, convert_fn: &str) -> TokenStream {
+        // spanned at the field's type so a missing `RedefinedConvert` impl points at
+        // the field
+        let convert_call = |value: TokenStream| {
+            let span = field.ty.span();
+            let crate_path = &self.crate_path;
+            let convert_fn = Ident::new(convert_fn, span);
+            quote_spanned! {span=> #crate_path::RedefinedConvert::#convert_fn(#value) }
+        };
+        let with_src = |attr: TypeAttribute| {
+            let expr = attr.nv_tokens.to_token_stream();
+            quote! {
+                {
+                    #[allow(unused_variables)]
+                    let src = #value;
+                    #expr
+                }
             }
         };
 
-        Ok(gen)
+        let attrs = &self.field_attrs[field_idx];
+        let custom_fn = if convert_fn == "from_source" { FROM_SOURCE_FN } else { TO_SOURCE_FN };
+        if let Some(attr) = custom_fn.find_type_attr(attrs) {
+            // custom conversions are used as is
+            with_src(attr)
+        } else if let Some(attr) = FIELD_FN
+            .find_type_attr(attrs)
+            .filter(|_| convert_fn == "from_source")
+        {
+            convert_call(with_src(attr))
+        } else {
+            convert_call(value.to_token_stream())
+        }
     }
 
-    pub fn to_source_tokens(&self, source_type: &Ident, target_type: &Ident) -> syn::Result<TokenStream> {
+    /// builds the match arm converting this variant of `from_type` into the
+    /// same variant of `to_type`, for every variant shape (unit, `{}`, `()`,
+    /// named and unnamed)
+    fn variant_arm(&self, from_type: &impl ToTokens, to_type: &impl ToTokens, convert_fn: &str) -> TokenStream {
         let variant_name = &self.variant.ident;
-        let gen = match &self.variant.fields {
+
+        match &self.variant.fields {
             Fields::Unit => quote! {
-                #target_type::#variant_name =>  #source_type::#variant_name,
+                #from_type::#variant_name => #to_type::#variant_name,
             },
             Fields::Named(FieldsNamed { named, .. }) => {
                 let field_names: Vec<_> = named
-                    .into_iter()
+                    .iter()
                     .map(|f| f.ident.as_ref().unwrap().clone())
                     .collect();
-                let cloned_field_names = field_names.clone();
+                // bound to hygienic variables, which can't collide with constants in scope
+                let field_vars: Vec<_> = (0..named.len())
+                    .map(|i| syn::Ident::new(&format!("x{}", i), Span::mixed_site()))
+                    .collect();
 
-                if !field_names.is_empty() {
-                    let field_mappings = cloned_field_names.into_iter().map(|field_name| {
-                        quote! { #field_name: redefined::RedefinedConvert::to_source(#field_name) }
+                let field_mappings = named
+                    .iter()
+                    .zip(field_names.iter().zip(&field_vars))
+                    .enumerate()
+                    .map(|(idx, (field, (field_name, field_var)))| {
+                        let converted = self.convert_field(idx, field, field_var, convert_fn);
+                        quote! { #field_name: #converted }
                     });
 
-                    quote! {
-                        #target_type::#variant_name { #( #field_names ),* } => #source_type::#variant_name { #( #field_mappings ),* },
-                    }
-                } else {
-                    TokenStream::new()
+                quote! {
+                    #from_type::#variant_name { #( #field_names: #field_vars ),* } => #to_type::#variant_name { #( #field_mappings ),* },
                 }
             }
             Fields::Unnamed(FieldsUnnamed { unnamed, .. }) => {
                 let field_vars: Vec<_> = (0..unnamed.len())
-                    .map(|i| syn::Ident::new(&format!("x{}", i), Span::call_site()))
+                    .map(|i| syn::Ident::new(&format!("x{}", i), Span::mixed_site()))
                     .collect();
 
-                let destructuring = quote! { (#(#field_vars),*) };
-
-                let construction = quote! {
-                    #source_type::#variant_name(#(redefined::RedefinedConvert::to_source(#field_vars)),*)
-                };
-
-                let unamed_gen = quote! {
-                    #target_type::#variant_name #destructuring => #construction,
-                };
+                let field_mappings = unnamed
+                    .iter()
+                    .zip(&field_vars)
+                    .enumerate()
+                    .map(|(idx, (field, field_var))| self.convert_field(idx, field, field_var, convert_fn));
 
-                unamed_gen
+                quote! {
+                    #from_type::#variant_name( #( #field_vars ),* ) => #to_type::#variant_name( #( #field_mappings ),* ),
+                }
             }
-        };
-
-        Ok(gen)
+        }
     }
 }
@@ -1,9 +1,11 @@
 pub mod r#enum;
 pub mod r#struct;
 
-use proc_macro2::TokenStream;
-use quote::{quote, ToTokens};
-use syn::{self, spanned::Spanned, Data, DataEnum, DataStruct, GenericParam, Generics, Ident};
+use std::collections::{HashMap, HashSet};
+
+use proc_macro2::{Group, TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::{self, Data, DataEnum, DataStruct, GenericParam, Generics, Ident, Path, PathArguments};
 
 #[cfg(feature = "unsafe")]
 use crate::attributes::symbol::TRANSMUTE;
@@ -14,25 +16,30 @@ use crate::{
 };
 
 pub struct RedefinedContainer {
-    source_type: Ident,
-    target_type: Ident,
-    source_generics_tokens: TokenStream,
-    target_generics: TokenStream,
+    crate_path:           Path,
+    source_type:          TokenStream,
+    target_type:          Ident,
+    target_generics:      TokenStream,
     impl_generics_tokens: TokenStream,
-    where_clause: Option<TokenStream>,
-    to_source_tokens: TokenStream,
-    from_source_tokens: TokenStream,
+    where_clause:         Option<TokenStream>,
+    to_source_tokens:     TokenStream,
+    from_source_tokens:   TokenStream,
 }
 
 impl RedefinedContainer {
-    pub fn parse_sub_containers(outer: OuterContainer, input_data: &Data, input_generics: &Generics) -> syn::Result<Self> {
-        let source_type = outer.source_type.clone().unwrap();
+    pub fn parse_sub_containers(outer: &OuterContainer, source_path: &Path, input_data: &Data, input_generics: &Generics) -> syn::Result<Self> {
+        let source_constructor = strip_path_arguments(source_path);
+        let crate_path = &outer.crate_path;
 
         let (mut to_source_tokens, mut from_source_tokens) = if outer.should_parse_fields() {
             let trait_container = match &input_data {
-                Data::Struct(data_struct) => TraitContainer::from_struct(data_struct, &source_type),
-                Data::Enum(data_enum) => TraitContainer::from_enum(data_enum, &source_type, &outer.target_type),
-                _ => return Err(syn::Error::new_spanned(source_type, "Expected an enum or struct")),
+                Data::Struct(data_struct) => {
+                    TraitContainer::from_struct(data_struct, source_path, &outer.source_types, &source_constructor, crate_path)
+                }
+                Data::Enum(data_enum) => {
+                    TraitContainer::from_enum(data_enum, source_path, &outer.source_types, &source_constructor, &outer.target_type, crate_path)
+                }
+                _ => return Err(syn::Error::new_spanned(source_path, "Expected an enum or struct")),
             }?;
             (trait_container.to_source, trait_container.from_source)
         } else {
@@ -40,69 +47,69 @@ impl RedefinedContainer {
         };
 
         if let Some(attr) = outer.get_symbol(TO_SOURCE_FN) {
-            to_source_tokens = attr.nv_tokens.unwrap().to_token_stream();
+            to_source_tokens = attr.nv_tokens.to_token_stream();
         }
 
         if let Some(attr) = outer.get_symbol(FROM_SOURCE_FN) {
-            from_source_tokens = attr.nv_tokens.unwrap().to_token_stream();
+            from_source_tokens = attr.nv_tokens.to_token_stream();
         }
 
-        let (target_generics, source_generics_tokens, impl_generics_tokens, where_clause) = if input_generics.type_params().count() == 0 {
-            let (impl_generics, ty_generics, _) = input_generics.split_for_impl();
-            (quote!(#ty_generics), quote!(#ty_generics), impl_generics.to_token_stream(), None)
+        let renames = source_generic_renames(input_generics, &reserved_idents(outer, input_data, input_generics));
+        let (target_generics, source_generics_tokens, impl_generics_tokens, where_clause) = split_generics(input_generics, &renames, crate_path)?;
+
+        // an explicitly instantiated source path is used as written, with the
+        // target's generic parameters swapped for their source counterparts
+        let source_type = if source_path
+            .segments
+            .last()
+            .map(|seg| seg.arguments.is_empty())
+            .unwrap_or(true)
+        {
+            quote!(#source_path #source_generics_tokens)
         } else {
-            let (_, ty_generics, _) = input_generics.split_for_impl();
-            let (modded_generics, checked_ty_generics, source_generics, where_clause) = build_generics_with_where_clause(input_generics.clone())?;
-            let (combined_impl_generics, ..) = modded_generics.split_for_impl();
-
-            let source_generics = if source_generics.is_empty() { quote!() } else { quote!(<#(#source_generics,)*>) };
-            let checked_ty_generics = if checked_ty_generics.is_empty() { quote!() } else { quote!(#ty_generics) };
-
-            if source_generics.is_empty() && checked_ty_generics.is_empty() {
-                (checked_ty_generics, source_generics, quote!(), None)
-            } else {
-                (checked_ty_generics, source_generics, combined_impl_generics.to_token_stream(), Some(where_clause))
-            }
+            instantiate_source_path(source_path, &renames)
         };
 
         #[cfg(feature = "unsafe")]
         if outer.get_symbol(TRANSMUTE).is_some() {
+            let tmp = Ident::new("tmp", proc_macro2::Span::mixed_site());
+
             from_source_tokens = quote! {
-                debug_assert_eq!(
-                    std::mem::size_of::<#source_type #source_generics_tokens>(),
-                    std::mem::size_of::<Self>()
+                ::core::debug_assert_eq!(
+                    ::core::mem::size_of::<#source_type>(),
+                    ::core::mem::size_of::<Self>()
                 );
 
                 unsafe {
-                    let mut tmp: std::mem::MaybeUninit<Self> = std::mem::MaybeUninit::uninit();
+                    let mut #tmp: ::core::mem::MaybeUninit<Self> = ::core::mem::MaybeUninit::uninit();
 
-                    std::ptr::copy_nonoverlapping(
-                        &src as *const #source_type #source_generics_tokens as *const u8,
-                        tmp.as_mut_ptr() as *mut u8,
-                        std::mem::size_of::<Self>()
+                    ::core::ptr::copy_nonoverlapping(
+                        &src as *const #source_type as *const u8,
+                        #tmp.as_mut_ptr() as *mut u8,
+                        ::core::mem::size_of::<Self>()
                     );
 
-                    tmp.assume_init()
+                    #tmp.assume_init()
                 }
             };
 
             to_source_tokens = quote! {
-                debug_assert_eq!(
-                    std::mem::size_of::<Self>(),
-                    std::mem::size_of::<#source_type #source_generics_tokens>()
+                ::core::debug_assert_eq!(
+                    ::core::mem::size_of::<Self>(),
+                    ::core::mem::size_of::<#source_type>()
                 );
 
                 unsafe {
-                    let mut tmp: std::mem::MaybeUninit<#source_type #source_generics_tokens>
-                        = std::mem::MaybeUninit::uninit();
+                    let mut #tmp: ::core::mem::MaybeUninit<#source_type>
+                        = ::core::mem::MaybeUninit::uninit();
 
-                    std::ptr::copy_nonoverlapping(
+                    ::core::ptr::copy_nonoverlapping(
                         &self as *const Self as *const u8,
-                        tmp.as_mut_ptr() as *mut u8,
-                        std::mem::size_of::<Self>()
+                        #tmp.as_mut_ptr() as *mut u8,
+                        ::core::mem::size_of::<Self>()
                     );
 
-                    tmp.assume_init()
+                    #tmp.assume_init()
                 }
 
 
@@ -110,9 +117,9 @@ impl RedefinedContainer {
         }
 
         Ok(Self {
+            crate_path: crate_path.clone(),
             source_type,
-            target_type: outer.target_type,
-            source_generics_tokens,
+            target_type: outer.target_type.clone(),
             target_generics,
             impl_generics_tokens,
             where_clause,
@@ -121,48 +128,90 @@ impl RedefinedContainer {
         })
     }
 
-    pub fn finalize(&self) -> TokenStream {
-        let Self {
+    /// parses a target composed from a tuple of source types, where each field
+    /// picks the part it's converted from with `#[redefined(from = ..)]`
+    pub fn parse_composed(outer: &OuterContainer, source_parts: &[Path], input_data: &Data, input_generics: &Generics) -> syn::Result<Self> {
+        let Data::Struct(data_struct) = input_data else {
+            return Err(syn::Error::new_spanned(&outer.target_type, "Composed source types are only supported for structs"));
+        };
+
+        #[cfg(feature = "unsafe")]
+        if outer.get_symbol(TRANSMUTE).is_some() {
+            return Err(syn::Error::new_spanned(&outer.target_type, "Cannot transmute a composed source type"));
+        }
+
+        let crate_path = &outer.crate_path;
+        let (mut to_source_tokens, mut from_source_tokens) = if outer.should_parse_fields() {
+            let container = StructContainer::parse_new(data_struct, source_parts, source_parts, true, crate_path)?;
+            (container.to_composed_source_tokens(source_parts)?, container.from_source_tokens()?)
+        } else {
+            (Default::default(), Default::default())
+        };
+
+        if let Some(attr) = outer.get_symbol(TO_SOURCE_FN) {
+            to_source_tokens = attr.nv_tokens.to_token_stream();
+        }
+
+        if let Some(attr) = outer.get_symbol(FROM_SOURCE_FN) {
+            from_source_tokens = attr.nv_tokens.to_token_stream();
+        }
+
+        let renames = source_generic_renames(input_generics, &reserved_idents(outer, input_data, input_generics));
+        let (target_generics, _, impl_generics_tokens, where_clause) = split_generics(input_generics, &renames, crate_path)?;
+
+        // each part is used as written, with the target's generic parameters
+        // swapped for their source counterparts
+        let source_parts = source_parts
+            .iter()
+            .map(|source_part| instantiate_source_path(source_part, &renames));
+        let source_type = quote!((#(#source_parts,)*));
+
+        Ok(Self {
+            crate_path: crate_path.clone(),
             source_type,
-            target_type,
-            source_generics_tokens,
+            target_type: outer.target_type.clone(),
             target_generics,
             impl_generics_tokens,
             where_clause,
             to_source_tokens,
             from_source_tokens,
-        } = self;
+        })
+    }
+
+    pub fn finalize(&self) -> TokenStream {
+        let Self { crate_path, source_type, target_type, target_generics, impl_generics_tokens, where_clause, to_source_tokens, from_source_tokens } =
+            self;
 
         let t = quote! {
-             impl #impl_generics_tokens redefined::RedefinedConvert<#source_type #source_generics_tokens> for #target_type #target_generics
+             impl #impl_generics_tokens #crate_path::RedefinedConvert<#source_type> for #target_type #target_generics
              #where_clause
                  {
                     #[allow(internal_features)]
-                     fn from_source(src: #source_type #source_generics_tokens) -> Self {
+                     fn from_source(src: #source_type) -> Self {
                             #from_source_tokens
                      }
 
                     #[allow(internal_features)]
-                     fn to_source(self) -> #source_type #source_generics_tokens {
+                     fn to_source(self) -> #source_type {
                             #to_source_tokens
                      }
                  }
 
-            impl #impl_generics_tokens From<#source_type #source_generics_tokens> for #target_type #target_generics
+            impl #impl_generics_tokens ::core::convert::From<#source_type> for #target_type #target_generics
             #where_clause
                 {
                     #[allow(internal_features)]
-                    fn from(src: #source_type #source_generics_tokens) -> Self {
-                        redefined::RedefinedConvert::from_source(src)
+                    fn from(src: #source_type) -> Self {
+                        #crate_path::RedefinedConvert::from_source(src)
                     }
                 }
 
-            impl #impl_generics_tokens Into<#source_type #source_generics_tokens> for #target_type #target_generics
+            impl #impl_generics_tokens ::core::convert::Into<#source_type> for #target_type #target_generics
             #where_clause
                 {
                     #[allow(internal_features)]
-                    fn into(self) -> #source_type #source_generics_tokens {
-                        redefined::RedefinedConvert::to_source(self)
+                    fn into(self) -> #source_type {
+                        #crate_path::RedefinedConvert::to_source(self)
                     }
                 }
 
@@ -174,85 +223,236 @@ impl RedefinedContainer {
 
 pub struct TraitContainer {
     pub from_source: TokenStream,
-    pub to_source: TokenStream,
+    pub to_source:   TokenStream,
 }
 
 impl TraitContainer {
-    pub fn from_struct(data_struct: &DataStruct, source_type: &Ident) -> syn::Result<Self> {
-        let container = StructContainer::parse_new(data_struct)?;
+    pub fn from_struct(
+        data_struct: &DataStruct,
+        source_path: &Path,
+        all_source_types: &[Path],
+        source_type: &Path,
+        crate_path: &Path,
+    ) -> syn::Result<Self> {
+        let container = StructContainer::parse_new(data_struct, std::slice::from_ref(source_path), all_source_types, false, crate_path)?;
 
         Ok(Self { from_source: container.from_source_tokens()?, to_source: container.to_source_tokens(source_type)? })
     }
 
-    pub fn from_enum(enum_struct: &DataEnum, source_type: &Ident, target_type: &Ident) -> syn::Result<Self> {
-        let container = EnumContainer::parse_new(enum_struct)?;
+    pub fn from_enum(
+        enum_struct: &DataEnum,
+        source_path: &Path,
+        all_source_types: &[Path],
+        source_type: &Path,
+        target_type: &Ident,
+        crate_path: &Path,
+    ) -> syn::Result<Self> {
+        let container = EnumContainer::parse_new(enum_struct, source_path, all_source_types, crate_path)?;
 
         Ok(Self {
             from_source: container.from_source_tokens(source_type, target_type)?,
-            to_source: container.to_source_tokens(source_type, target_type)?,
+            to_source:   container.to_source_tokens(source_type, target_type)?,
         })
     }
 }
 
-pub fn build_generics_with_where_clause(ty_generics: Generics) -> syn::Result<(Generics, Vec<GenericParam>, Vec<GenericParam>, TokenStream)> {
-    let source_generics = ty_generics
-        .params
-        .iter()
-        .filter_map(|target_generic| {
-            let mut source_generic = target_generic.clone();
-            if let GenericParam::Type(s) = &mut source_generic {
-                if s.default.is_some() {
-                    s.default = None;
-                    s.eq_token = None;
-                    return None;
+/// splits the target's generics into the target's generic arguments, the
+/// source's generic arguments, the impl generics and the where clause of the
+/// generated impls
+fn split_generics(
+    input_generics: &Generics,
+    renames: &HashMap<String, Ident>,
+    crate_path: &Path,
+) -> syn::Result<(TokenStream, TokenStream, TokenStream, Option<TokenStream>)> {
+    let split = if input_generics.type_params().count() == 0 {
+        let (impl_generics, ty_generics, where_clause) = input_generics.split_for_impl();
+        (quote!(#ty_generics), quote!(#ty_generics), impl_generics.to_token_stream(), where_clause.map(|w| w.to_token_stream()))
+    } else {
+        let (_, ty_generics, _) = input_generics.split_for_impl();
+        let (modded_generics, source_generics, where_clause) = build_generics_with_where_clause(input_generics, renames, crate_path)?;
+        let (combined_impl_generics, ..) = modded_generics.split_for_impl();
+
+        let source_generics = if source_generics.is_empty() { quote!() } else { quote!(<#(#source_generics),*>) };
+
+        (quote!(#ty_generics), source_generics, combined_impl_generics.to_token_stream(), Some(where_clause))
+    };
+
+    Ok(split)
+}
+
+/// removes the generic arguments from the last segment of a path so it can be
+/// used to construct or match on the source type (i.e. `ruint::Uint<256, 4>`
+/// -> `ruint::Uint`)
+pub fn strip_path_arguments(path: &Path) -> Path {
+    let mut path = path.clone();
+    if let Some(seg) = path.segments.last_mut() {
+        seg.arguments = PathArguments::None;
+    }
+
+    path
+}
+
+/// rewrites the generic arguments of an explicitly instantiated source path so
+/// that the target's type parameters refer to their source counterparts (i.e.
+/// `GenericTypeStruct<u128, Y>` -> `GenericTypeStruct<u128, YR>`), allowing the
+/// target to keep a subset of the source's parameters in any order
+pub fn instantiate_source_path(source_path: &Path, renames: &HashMap<String, Ident>) -> TokenStream {
+    let stripped_path = strip_path_arguments(source_path);
+    let arguments = source_path
+        .segments
+        .last()
+        .map(|seg| seg.arguments.to_token_stream())
+        .unwrap_or_default();
+    let arguments = rename_idents(arguments, renames);
+
+    quote!(#stripped_path #arguments)
+}
+
+fn rename_idents(tokens: TokenStream, renames: &HashMap<String, Ident>) -> TokenStream {
+    let mut is_lifetime = false;
+    tokens
+        .into_iter()
+        .map(|token| {
+            let renamed = match token {
+                TokenTree::Ident(ident) if !is_lifetime => TokenTree::Ident(renames.get(&ident.to_string()).cloned().unwrap_or(ident)),
+                TokenTree::Group(group) => {
+                    let mut renamed_group = Group::new(group.delimiter(), rename_idents(group.stream(), renames));
+                    renamed_group.set_span(group.span());
+                    TokenTree::Group(renamed_group)
                 }
-                s.ident = Ident::new(&format!("{}R", s.ident), target_generic.span());
+                other => other,
+            };
+            is_lifetime = matches!(&renamed, TokenTree::Punct(p) if p.as_char() == '\'');
+
+            renamed
+        })
+        .collect()
+}
+
+/// the target's (non-defaulted) type parameters mapped to the names of their
+/// source counterparts (i.e. `X` -> `XR`), which don't collide with any
+/// identifier in `reserved` (i.e. `XR0` if `XR` is taken)
+pub fn source_generic_renames(generics: &Generics, reserved: &HashSet<String>) -> HashMap<String, Ident> {
+    let mut reserved = reserved.clone();
+    generics
+        .type_params()
+        .filter(|p| p.default.is_none())
+        .map(|p| {
+            let mut rename = format_ident!("{}R", p.ident, span = p.ident.span());
+            let mut idx = 0;
+            while reserved.contains(&rename.to_string()) {
+                rename = format_ident!("{}R{idx}", p.ident, span = p.ident.span());
+                idx += 1;
             }
+            reserved.insert(rename.to_string());
 
-            Some(source_generic)
+            (p.ident.to_string(), rename)
         })
-        .collect::<Vec<_>>();
+        .collect()
+}
 
-    let check_ty_generics = ty_generics
-        .params
+/// the identifiers used by the target type, it's attributes and source types,
+/// which generated generic parameters can't shadow
+fn reserved_idents(outer: &OuterContainer, input_data: &Data, input_generics: &Generics) -> HashSet<String> {
+    let mut idents = HashSet::new();
+    let data_tokens = match input_data {
+        Data::Struct(data_struct) => data_struct.fields.to_token_stream(),
+        Data::Enum(data_enum) => data_enum.variants.to_token_stream(),
+        Data::Union(data_union) => data_union.fields.to_token_stream(),
+    };
+    collect_idents(data_tokens, &mut idents);
+    collect_idents(input_generics.to_token_stream(), &mut idents);
+    collect_idents(input_generics.where_clause.to_token_stream(), &mut idents);
+    for source_path in outer
+        .source_types
         .iter()
-        .filter_map(|target_generic| {
-            let target_generic = target_generic.clone();
-            if let GenericParam::Type(ref s) = target_generic {
-                if s.default.is_some() {
-                    return None;
-                }
+        .chain(outer.composed_types.iter().flatten())
+    {
+        collect_idents(source_path.to_token_stream(), &mut idents);
+    }
+    for attr in &outer.container_attrs {
+        collect_idents(attr.nv_tokens.to_token_stream(), &mut idents);
+    }
+
+    idents
+}
+
+fn collect_idents(tokens: TokenStream, idents: &mut HashSet<String>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => {
+                idents.insert(ident.to_string());
             }
+            TokenTree::Group(group) => collect_idents(group.stream(), idents),
+            _ => (),
+        }
+    }
+}
+
+/// builds the combined impl generics, the generic arguments of the source type
+/// and the where clause of the generated impls
+///
+/// the bounds on the target's type parameters and its `where` predicates are
+/// kept, and repeated for the source parameters with the renamed parameters
+pub fn build_generics_with_where_clause(
+    ty_generics: &Generics,
+    renames: &HashMap<String, Ident>,
+    crate_path: &Path,
+) -> syn::Result<(Generics, Vec<TokenStream>, TokenStream)> {
+    let source_type_params = ty_generics
+        .type_params()
+        .filter(|p| p.default.is_none())
+        .map(|p| syn::parse2::<GenericParam>(rename_idents(p.to_token_stream(), renames)))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let source_generics = ty_generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Lifetime(l) => Some(l.lifetime.to_token_stream()),
+            GenericParam::Const(c) => Some(c.ident.to_token_stream()),
+            GenericParam::Type(t) => renames
+                .get(&t.ident.to_string())
+                .map(|s| s.to_token_stream()),
+        })
+        .collect::<Vec<_>>();
 
-            Some(target_generic)
+    let convert_predicates = ty_generics
+        .type_params()
+        .filter_map(|p| {
+            renames.get(&p.ident.to_string()).map(|s| {
+                let t = &p.ident;
+                quote! { #t: #crate_path::RedefinedConvert<#s> }
+            })
         })
         .collect::<Vec<_>>();
 
-    // + From<#s> + Into<#s>,
-    let zip_generics = source_generics
+    let target_predicates = ty_generics
+        .where_clause
         .iter()
-        .zip(ty_generics.params.clone())
-        .filter_map(|(source, target)| {
-            let mut target = target;
-            if let GenericParam::Type(s) = &mut target {
-                if s.default.is_some() {
-                    s.default = None;
-                    s.eq_token = None;
-                    return None;
-                }
-            }
-            let (s, t) = (source.to_token_stream(), target.to_token_stream());
-            Some(quote! { #t: redefined::RedefinedConvert<#s>  })
+        .flat_map(|w| w.predicates.iter())
+        .map(|p| p.to_token_stream())
+        .collect::<Vec<_>>();
+
+    let source_predicates = target_predicates
+        .iter()
+        .map(|p| rename_idents(p.clone(), renames))
+        .filter(|p| {
+            !target_predicates
+                .iter()
+                .any(|t| t.to_string() == p.to_string())
         })
         .collect::<Vec<_>>();
 
     let where_clause = quote! {
        where
-           #(#zip_generics),*
+           #(#target_predicates,)*
+           #(#source_predicates,)*
+           #(#convert_predicates),*
     };
 
     let mut target_generics = ty_generics.clone();
-    target_generics.params.extend(source_generics.clone());
+    target_generics.params.extend(source_type_params);
 
-    Ok((target_generics, check_ty_generics, source_generics, where_clause))
+    Ok((target_generics, source_generics, where_clause))
 }
@@ -1,8 +1,12 @@
 use proc_macro2::TokenStream;
-use quote::quote;
-use syn::{self, parse::Parse, DataStruct, Field, Fields, Ident};
+use quote::{quote, quote_spanned, ToTokens};
+use syn::{self, parse::Parse, spanned::Spanned, DataStruct, Field, Fields, Ident, Path};
 
-use crate::attributes::{symbol::FIELD_FN, type_attr::TypeAttribute, ContainerAttributes};
+use crate::attributes::{
+    symbol::{FIELD_FN, FROM_PART, FROM_SOURCE_FN, SOURCE, TO_SOURCE_FN},
+    type_attr::TypeAttribute,
+    ContainerAttributes,
+};
 
 pub struct StructContainer {
     pub fields:   Vec<StructField>,
@@ -10,7 +14,19 @@ pub struct StructContainer {
 }
 
 impl StructContainer {
-    pub fn parse_new(data_struct: &DataStruct) -> syn::Result<Self> {
+    /// parses the fields of the struct, when `is_composed` each field is
+    /// converted from the part of the source tuple given by it's `from = ..`
+    /// attribute (or the first part by default)
+    ///
+    /// `all_source_types` are every source type of the target, which `source =
+    /// ..` field attributes must refer to unambiguously
+    pub fn parse_new(
+        data_struct: &DataStruct,
+        source_types: &[Path],
+        all_source_types: &[Path],
+        is_composed: bool,
+        crate_path: &Path,
+    ) -> syn::Result<Self> {
         let (fields, is_named) = match &data_struct.fields {
             Fields::Named(fields_named) => (&fields_named.named, true),
             Fields::Unnamed(fields_unnamed) => (&fields_unnamed.unnamed, false),
@@ -22,8 +38,8 @@ impl StructContainer {
                 .iter()
                 .flat_map(|field| {
                     field.ident.as_ref().map(|idt| {
-                        let mut this = StructField::new(Some(idt.clone()), field.clone(), None);
-                        this.parse_attributes_for_field()?;
+                        let mut this = StructField::new(Some(idt.clone()), field.clone(), None, crate_path.clone());
+                        this.parse_attributes_for_field(source_types, all_source_types, is_composed)?;
                         Ok(this)
                     })
                 })
@@ -33,14 +49,34 @@ impl StructContainer {
                 .iter()
                 .enumerate()
                 .map(|(unnamed_idx, field)| {
-                    let mut this = StructField::new(None, field.clone(), Some(unnamed_idx));
-                    this.parse_attributes_for_field()?;
+                    let mut this = StructField::new(None, field.clone(), Some(unnamed_idx), crate_path.clone());
+                    this.parse_attributes_for_field(source_types, all_source_types, is_composed)?;
                     Ok(this)
                 })
                 .collect::<syn::Result<Vec<_>>>()?
         };
 
-        Ok(Self { fields: struct_fields, is_named })
+        let mut this = Self { fields: struct_fields, is_named };
+        if is_composed && !is_named {
+            this.index_unnamed_source_parts();
+        }
+
+        Ok(this)
+    }
+
+    /// sets the index of each unnamed field within it's part of the source
+    /// tuple
+    fn index_unnamed_source_parts(&mut self) {
+        let mut part_lens = Vec::new();
+        for field in self.fields.iter_mut() {
+            let part = field.source_part.unwrap_or_default();
+            if part_lens.len() <= part {
+                part_lens.resize(part + 1, 0);
+            }
+
+            field.source_unnamed_idx = Some(part_lens[part]);
+            part_lens[part] += 1;
+        }
     }
 
     pub fn from_source_tokens(&self) -> syn::Result<TokenStream> {
@@ -66,10 +102,32 @@ impl StructContainer {
         Ok(gen)
     }
 
-    pub fn to_source_tokens(&self, source_type: &Ident) -> syn::Result<TokenStream> {
-        let tokens = self
-            .fields
+    pub fn to_source_tokens(&self, source_type: &Path) -> syn::Result<TokenStream> {
+        self.to_source_part_tokens(source_type, self.fields.iter())
+    }
+
+    /// rebuilds each part of a composed source tuple from the fields converted
+    /// from it
+    pub fn to_composed_source_tokens(&self, source_parts: &[Path]) -> syn::Result<TokenStream> {
+        let parts = source_parts
             .iter()
+            .enumerate()
+            .map(|(part_idx, source_part)| {
+                let source_part = super::strip_path_arguments(source_part);
+                let fields = self
+                    .fields
+                    .iter()
+                    .filter(|field| field.source_part.unwrap_or_default() == part_idx);
+
+                self.to_source_part_tokens(&source_part, fields)
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        Ok(quote! { (#(#parts,)*) })
+    }
+
+    fn to_source_part_tokens<'a>(&self, source_type: &Path, fields: impl Iterator<Item = &'a StructField>) -> syn::Result<TokenStream> {
+        let tokens = fields
             .map(|field| field.to_source_tokens())
             .collect::<syn::Result<Vec<_>>>()?;
 
@@ -91,50 +149,141 @@ impl StructContainer {
 }
 
 pub struct StructField {
-    pub ident:          Option<Ident>,
-    pub field:          Field,
-    pub field_attrs:    Vec<TypeAttribute>,
-    pub is_unnamed_idx: Option<usize>,
+    pub ident:              Option<Ident>,
+    pub field:              Field,
+    pub field_attrs:        Vec<TypeAttribute>,
+    pub is_unnamed_idx:     Option<usize>,
+    /// the part of a composed source tuple this field is converted from
+    pub source_part:        Option<usize>,
+    /// the index of an unnamed field within it's part of a composed source
+    pub source_unnamed_idx: Option<usize>,
+    /// the path of the `redefined` crate
+    pub crate_path:         Path,
 }
 
 impl StructField {
-    pub fn new(ident: Option<Ident>, field: Field, is_unnamed_idx: Option<usize>) -> Self {
-        Self { ident, field, field_attrs: Vec::new(), is_unnamed_idx }
+    pub fn new(ident: Option<Ident>, field: Field, is_unnamed_idx: Option<usize>, crate_path: Path) -> Self {
+        Self { ident, field, field_attrs: Vec::new(), is_unnamed_idx, source_part: None, source_unnamed_idx: None, crate_path }
     }
 
-    pub fn parse_attributes_for_field(&mut self) -> syn::Result<()> {
+    /// parses the field's attributes, skipping those specific to a different
+    /// source type (i.e. `#[redefined(func = "..", source = A)]`)
+    pub fn parse_attributes_for_field(&mut self, source_types: &[Path], all_source_types: &[Path], is_composed: bool) -> syn::Result<()> {
         let mut attrs = Vec::new();
         for attr in &self.field.attrs {
             if attr.path().is_ident("redefined") {
-                attrs.extend(attr.parse_args_with(ContainerAttributes::parse)?.0);
+                let field_attrs = attr.parse_args_with(ContainerAttributes::parse)?.0;
+                if let Some(source) = SOURCE.find_type_attr(&field_attrs) {
+                    let mut matches = false;
+                    for source_type in source_types {
+                        matches |= source.matches_source(source_type, all_source_types)?;
+                    }
+                    if !matches {
+                        continue;
+                    }
+                }
+
+                if let Some(from) = FROM_PART.find_type_attr(&field_attrs) {
+                    let part = from.part_index()?;
+                    if !is_composed {
+                        return Err(syn::Error::new_spanned(
+                            attr,
+                            "`from = ..` can only be used with a composed source type (i.e. `#[redefined((A, B))]`)",
+                        ));
+                    } else if part >= source_types.len() {
+                        return Err(syn::Error::new_spanned(
+                            attr,
+                            format!("`from = {part}` is out of bounds for {} source types", source_types.len()),
+                        ));
+                    }
+                    self.source_part = Some(part);
+                }
+
+                attrs.extend(
+                    field_attrs
+                        .into_iter()
+                        .filter(|a| a.symbol != SOURCE && a.symbol != FROM_PART),
+                );
             }
         }
+        if is_composed && self.source_part.is_none() {
+            self.source_part = Some(0);
+        }
         self.field_attrs = attrs;
 
         Ok(())
     }
 
+    /// the source value this field is converted from, (i.e. `src` or `src.1`
+    /// for a composed source)
+    fn source_value(&self) -> TokenStream {
+        match self.source_part {
+            Some(part) => {
+                let part = syn::Index::from(part);
+                quote! { src.#part }
+            }
+            None => quote! { src },
+        }
+    }
+
+    /// calls the trait's conversion function on a value, spanned at the field's
+    /// type so a missing `RedefinedConvert` impl points at the field
+    fn convert_call(&self, convert_fn: &str, value: TokenStream) -> TokenStream {
+        let span = self.field.ty.span();
+        let convert_fn = Ident::new(convert_fn, span);
+        let crate_path = &self.crate_path;
+        quote_spanned! {span=> #crate_path::RedefinedConvert::#convert_fn(#value) }
+    }
+
     pub fn from_source_tokens(&self) -> syn::Result<TokenStream> {
         let fields_attrs = &self.field_attrs;
         let ident = &self.ident;
 
-        let gen = if FIELD_FN.contained_in(fields_attrs) {
-            let attr = FIELD_FN
-                .find_type_attr(fields_attrs)
-                .ok_or(syn::Error::new_spanned(&self.ident, "FIELD FN ERROR"))?;
+        let field_attr = self
+            .field
+            .attrs
+            .iter()
+            .find(|a| a.path().is_ident("redefined"));
+        if let Some(attr) = fields_attrs
+            .iter()
+            .find(|attr| ![FIELD_FN, FROM_SOURCE_FN, TO_SOURCE_FN].contains(&attr.symbol))
+        {
+            return Err(syn::Error::new_spanned(
+                field_attr,
+                format!("The `{}` attribute can't be used on a field of a type with a source type", attr.symbol),
+            ));
+        }
 
-            let func_name = attr.nv_tokens.unwrap();
+        if FIELD_FN.contained_in(fields_attrs) && FROM_SOURCE_FN.contained_in(fields_attrs) {
+            return Err(syn::Error::new_spanned(field_attr, "Cannot have both `func` and `from_source` attributes on a field"));
+        }
 
-            quote! { #ident: redefined::RedefinedConvert::from_source(#func_name), }
-        } else if fields_attrs.is_empty() {
-            if let Some(idx) = self.is_unnamed_idx {
-                let index = syn::Index::from(idx);
-                quote! { redefined::RedefinedConvert::from_source(src.#index)}
+        let gen = if let Some(attr) = FROM_SOURCE_FN.find_type_attr(fields_attrs) {
+            // custom conversions are used as is
+            let from_source = attr.nv_tokens.to_token_stream();
+
+            if self.is_unnamed_idx.is_some() {
+                from_source
             } else {
-                quote! { #ident: redefined::RedefinedConvert::from_source(src.#ident),}
+                quote! { #ident: #from_source, }
+            }
+        } else if let Some(attr) = FIELD_FN.find_type_attr(fields_attrs) {
+            let from_source = self.convert_call("from_source", attr.nv_tokens.to_token_stream());
+
+            if self.is_unnamed_idx.is_some() {
+                from_source
+            } else {
+                quote! { #ident: #from_source, }
             }
         } else {
-            unreachable!("cannot reach - should be no more field attrs: {:?}", fields_attrs);
+            let source_value = self.source_value();
+            if let Some(idx) = self.source_unnamed_idx.or(self.is_unnamed_idx) {
+                let index = syn::Index::from(idx);
+                self.convert_call("from_source", quote!(#source_value.#index))
+            } else {
+                let from_source = self.convert_call("from_source", quote!(#source_value.#ident));
+                quote! { #ident: #from_source, }
+            }
         };
 
         Ok(gen)
@@ -143,11 +292,20 @@ impl StructField {
     pub fn to_source_tokens(&self) -> syn::Result<TokenStream> {
         let matched_field = &self.ident;
 
-        let gen = if let Some(idx) = self.is_unnamed_idx {
+        let gen = if let Some(attr) = TO_SOURCE_FN.find_type_attr(&self.field_attrs) {
+            let to_source = attr.nv_tokens.to_token_stream();
+
+            if self.is_unnamed_idx.is_some() {
+                to_source
+            } else {
+                quote! { #matched_field: #to_source, }
+            }
+        } else if let Some(idx) = self.is_unnamed_idx {
             let index = syn::Index::from(idx);
-            quote! { redefined::RedefinedConvert::to_source(self.#index) }
+            self.convert_call("to_source", quote!(self.#index))
         } else {
-            quote! { #matched_field: redefined::RedefinedConvert::to_source(self.#matched_field), }
+            let to_source = self.convert_call("to_source", quote!(self.#matched_field));
+            quote! { #matched_field: #to_source, }
         };
 
         Ok(gen)
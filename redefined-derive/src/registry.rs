@@ -0,0 +1,261 @@
+//! the names and modules of new types, recorded while the crate is expanded so
+//! the new types referencing them use them
+//!
+//! the registry lives in the process of the compiler expanding the crate, so a
+//! type is only known to the types expanded after it, and tools keeping the
+//! macros loaded across edits (i.e. rust-analyzer) can use names recorded by
+//! an earlier version of the code. Types whose new type is renamed or moved
+//! into a module can always be mapped explicitly with `map_types(..)` or
+//! `field(..)` instead
+
+use std::{collections::HashSet, path::PathBuf, sync::Mutex};
+
+use quote::ToTokens;
+use syn::{Ident, Path, PathSegment};
+
+use crate::config::Config;
+
+/// the names given to new types (i.e. `#[redefined_attr(name = "U256")]` or
+/// `redefined_remote!([Uint as U256Mirror] : "ruint")`) and the modules they're
+/// generated in (i.e. `#[redefined_attr(module = "mirror")]`)
+static NAMED_TYPES: Mutex<Vec<NamedType>> = Mutex::new(Vec::new());
+
+#[derive(Debug, Clone, PartialEq)]
+struct NamedType {
+    /// the crate being compiled
+    crate_name: String,
+    /// the paths of the source type (i.e. `["ruint", "Uint"]`), only the
+    /// identifier of a type derived in the crate being compiled
+    paths:      Vec<Vec<String>>,
+    name:       Option<String>,
+    module:     Option<String>,
+}
+
+/// the crates the crate being compiled depends on, by the manifest they're
+/// read from
+static EXTERN_CRATES: Mutex<Option<(PathBuf, HashSet<String>)>> = Mutex::new(None);
+
+/// the crate being compiled
+fn crate_name() -> String {
+    std::env::var("CARGO_CRATE_NAME").unwrap_or_default()
+}
+
+/// records the name and module of the new type of a type derived in the crate
+/// being compiled, by it's identifier
+///
+/// types with the same identifier but different new types are all recorded, so
+/// references to them are ambiguous
+pub fn record_new_type(ident: &Ident, name: Option<&Ident>, module: Option<&Ident>) {
+    if name.is_none() && module.is_none() {
+        return;
+    }
+
+    let named_type = NamedType::new(vec![vec![ident.to_string()]], name, module);
+    let mut named_types = NAMED_TYPES.lock().unwrap_or_else(|e| e.into_inner());
+    if !named_types.contains(&named_type) {
+        named_types.push(named_type);
+    }
+}
+
+/// records the name and module of the new type of a remote type, by the paths
+/// of the type (i.e. `alloy_primitives::U256` and `ruint::Uint`)
+///
+/// a remote type mirrored again replaces the earlier new type, so the types
+/// referencing it use the last new type expanded before them
+pub fn record_remote_type(paths: &[Vec<String>], name: Option<&Ident>, module: Option<&Ident>) {
+    let named_type = NamedType::new(paths.to_vec(), name, module);
+    let mut named_types = NAMED_TYPES.lock().unwrap_or_else(|e| e.into_inner());
+    named_types.retain(|other| other.crate_name != named_type.crate_name || !other.paths.iter().any(|path| paths.contains(path)));
+    named_types.push(named_type);
+}
+
+impl NamedType {
+    fn new(paths: Vec<Vec<String>>, name: Option<&Ident>, module: Option<&Ident>) -> Self {
+        Self { crate_name: crate_name(), paths, name: name.map(Ident::to_string), module: module.map(Ident::to_string) }
+    }
+}
+
+/// the recorded new type of a type, by it's path as written (i.e. `Foo`,
+/// `a::Foo` or `ruint::Uint<256, 4>`)
+///
+/// the path matches a recorded path if either ends with the other, an exact
+/// match takes precedence. Errors if it matches the types of different new
+/// types (i.e. `Foo` for `a::Foo as FooA` and `b::Foo as FooB`)
+fn named_type(path: &Path) -> syn::Result<Option<NamedType>> {
+    let written = path
+        .segments
+        .iter()
+        .map(|seg| seg.ident.to_string())
+        .skip_while(|seg| ["crate", "self", "super"].contains(&seg.as_str()))
+        .collect::<Vec<_>>();
+    if written.is_empty() {
+        return Ok(None);
+    }
+
+    let crate_name = crate_name();
+    let named_types = NAMED_TYPES.lock().unwrap_or_else(|e| e.into_inner());
+    let candidates = named_types
+        .iter()
+        .filter(|named_type| named_type.crate_name == crate_name)
+        .filter(|named_type| {
+            named_type
+                .paths
+                .iter()
+                .any(|recorded| recorded.ends_with(&written) || written.ends_with(recorded))
+        })
+        .collect::<Vec<_>>();
+    let exact = candidates
+        .iter()
+        .copied()
+        .filter(|named_type| named_type.paths.contains(&written))
+        .collect::<Vec<_>>();
+
+    let mut matched = Vec::<&NamedType>::new();
+    for named_type in if exact.is_empty() { candidates } else { exact } {
+        if !matched
+            .iter()
+            .any(|other| other.name == named_type.name && other.module == named_type.module)
+        {
+            matched.push(named_type);
+        }
+    }
+    match matched.as_slice() {
+        [] => Ok(None),
+        [named_type] => Ok(Some((*named_type).clone())),
+        matched => {
+            let new_types = matched
+                .iter()
+                .map(|named_type| {
+                    let path = named_type
+                        .paths
+                        .first()
+                        .map(|path| path.join("::"))
+                        .unwrap_or_default();
+                    let name = named_type
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| "default name".to_string());
+                    match &named_type.module {
+                        Some(module) => format!("`{path}` (`{module}::{name}`)"),
+                        None => format!("`{path}` (`{name}`)"),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            Err(syn::Error::new_spanned(
+                path,
+                format!(
+                    "Ambiguous type `{}`, it matches {new_types}, use the full path of a remote type or map it with `map_types(..)`",
+                    path.to_token_stream().to_string().replace(' ', "")
+                ),
+            ))
+        }
+    }
+}
+
+/// the default name of the new type of a type, `<prefix><TYPE><suffix>` from
+/// the config
+pub fn new_type_name(ident: &Ident) -> syn::Result<Ident> {
+    Ok(Config::load()?.redefined_name(ident))
+}
+
+/// the path of the new type of a type from the module the type's new type is
+/// referenced in, it's recorded module and name (i.e. `mirror::U256Mirror` for
+/// `ruint::Uint`) or it's default name
+pub fn new_type_path(path: &Path) -> syn::Result<Path> {
+    let Some(ident) = path.segments.last().map(|seg| seg.ident.clone()) else { return Ok(path.clone()) };
+    let named_type = named_type(path)?;
+
+    let name = match named_type
+        .as_ref()
+        .and_then(|named_type| named_type.name.as_ref())
+    {
+        Some(name) => Ident::new(name, ident.span()),
+        None => new_type_name(&ident)?,
+    };
+    let mut new_type_path = Path::from(name);
+    if let Some(module) = named_type.and_then(|named_type| named_type.module) {
+        new_type_path
+            .segments
+            .insert(0, PathSegment::from(Ident::new(&module, ident.span())));
+    }
+
+    Ok(new_type_path)
+}
+
+/// rewrites the last segment of a path to the new type's name, inserting the
+/// module the new type is generated in (i.e. `other::Foo` ->
+/// `other::mirror::FooRedefined`)
+///
+/// the new types of types from other crates are generated in the crate being
+/// compiled, so their crate's path is dropped (i.e. `ruint::Uint<256, 4>` ->
+/// `UintRedefined<256, 4>`)
+pub fn redefine_path_segment(path: &mut Path) -> syn::Result<()> {
+    let Some(arguments) = path.segments.last().map(|seg| seg.arguments.clone()) else { return Ok(()) };
+    let new_type_path = new_type_path(path)?;
+
+    if is_extern_path(path) {
+        path.segments.clear();
+        path.leading_colon = None;
+    } else {
+        path.segments.pop();
+    }
+    path.segments.extend(new_type_path.segments);
+    if let Some(seg) = path.segments.last_mut() {
+        seg.arguments = arguments;
+    }
+
+    Ok(())
+}
+
+/// whether a path starts with another crate (i.e. `::ruint::Uint` or
+/// `ruint::Uint` with `ruint` in the dependencies of the crate being compiled)
+fn is_extern_path(path: &Path) -> bool {
+    if path.segments.len() < 2 {
+        return false;
+    }
+    if path.leading_colon.is_some() {
+        return true;
+    }
+
+    let first = path.segments[0].ident.to_string();
+    ["std", "core", "alloc"].contains(&first.as_str()) || extern_crates().contains(&first)
+}
+
+/// the names the dependencies of the crate being compiled are used with (i.e.
+/// `alloy_primitives`, or the name of a renamed dependency), from it's
+/// `[dependencies]`, `[dev-dependencies]` and `[build-dependencies]`
+fn extern_crates() -> HashSet<String> {
+    let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") else { return HashSet::new() };
+    let manifest_path = PathBuf::from(manifest_dir).join("Cargo.toml");
+
+    let mut cached = EXTERN_CRATES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((cached_path, crates)) = cached.as_ref() {
+        if *cached_path == manifest_path {
+            return crates.clone();
+        }
+    }
+
+    let manifest = std::fs::read_to_string(&manifest_path)
+        .ok()
+        .and_then(|contents| contents.parse::<toml::Table>().ok())
+        .unwrap_or_default();
+    let target_tables = manifest
+        .get("target")
+        .and_then(|target| target.as_table())
+        .into_iter()
+        .flat_map(|target| target.values())
+        .filter_map(|target| target.as_table());
+
+    let crates = std::iter::once(&manifest)
+        .chain(target_tables)
+        .flat_map(|table| ["dependencies", "dev-dependencies", "build-dependencies"].map(|key| table.get(key)))
+        .flatten()
+        .filter_map(|dependencies| dependencies.as_table())
+        .flat_map(|dependencies| dependencies.keys())
+        .map(|name| name.replace('-', "_"))
+        .collect::<HashSet<_>>();
+    *cached = Some((manifest_path, crates.clone()));
+
+    crates
+}
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{
+    parse_quote,
+    visit_mut::{self, VisitMut},
+    DeriveInput, Expr, GenericArgument, GenericParam, Generics, Ident, Lifetime, Path, PathArguments, Type, TypePath, Visibility,
+};
+
+use super::{
+    file_parser::{ParsedRemoteAlias, ParsedRemoteType, RemoteItem},
+    resolver::Resolver,
+};
+use crate::{attributes::type_map::TypeMap, new_types::r#struct::parse_type_to_redefined};
+
+/// how many aliases of aliases are followed before giving up
+const MAX_ALIAS_DEPTH: usize = 16;
+
+/// the definition of a remote type, and the type alias it was named by (i.e.
+/// `Uint` for `U256`)
+#[derive(Debug, Clone)]
+pub struct RemoteDefinition {
+    pub definition: ParsedRemoteType,
+    pub alias:      Option<AliasedType>,
+}
+
+/// a type alias of a remote type
+#[derive(Debug, Clone)]
+pub struct AliasedType {
+    pub alias: ParsedRemoteAlias,
+    /// the generic arguments the alias gives the definition, in terms of the
+    /// alias's generics (i.e. `256, 4` for `pub type U256 = Uint<256, 4>;`)
+    pub args:  Vec<GenericArgument>,
+}
+
+/// follows a type alias (and aliases of aliases) to the definition of the type
+/// it aliases
+pub fn expand_alias(resolver: &mut Resolver, alias: ParsedRemoteAlias, span: Span) -> syn::Result<RemoteDefinition> {
+    let alias_name = alias.item.ident.to_string();
+    let mut target = (*alias.item.ty).clone();
+    let (mut package_name, mut module_path) = (alias.package_name.clone(), alias.module_path.clone());
+
+    for _ in 0..MAX_ALIAS_DEPTH {
+        let Type::Path(TypePath { qself: None, path }) = &target else {
+            return Err(syn::Error::new(span, format!("The type alias `{alias_name}` must alias a struct or enum")));
+        };
+        let segments = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>();
+        let args = generic_args(path);
+
+        match resolver
+            .resolve(&package_name, &module_path, &segments)
+            .as_slice()
+        {
+            [RemoteItem::Type(definition)] => {
+                return Ok(RemoteDefinition { definition: definition.clone(), alias: Some(AliasedType { alias, args }) })
+            }
+            [RemoteItem::Alias(inner)] => {
+                target = substitute_type(&inner.item.ty, &inner.item.generics, &args, span)?;
+                (package_name, module_path) = (inner.package_name.clone(), inner.module_path.clone());
+            }
+            [] => return Err(syn::Error::new(span, format!("Could not find the definition of `{}` aliased by `{alias_name}`", segments.join("::")))),
+            results => {
+                let candidates = results
+                    .iter()
+                    .map(|result| format!("`{}`", result.item_path()))
+                    .collect::<Vec<_>>();
+                return Err(syn::Error::new(
+                    span,
+                    format!("Too Many Definitions Of `{}` Aliased By `{alias_name}`: {}", segments.join("::"), candidates.join(", ")),
+                ));
+            }
+        }
+    }
+
+    Err(syn::Error::new(span, format!("Too many nested aliases for `{alias_name}`")))
+}
+
+impl RemoteDefinition {
+    /// the full path of the type, or of the alias it was named by
+    pub fn item_path(&self) -> String {
+        match &self.alias {
+            Some(aliased) => aliased.alias.item_path(),
+            None => self.definition.item_path(),
+        }
+    }
+
+    /// the definition with the alias's generic arguments substituted, named as
+    /// the alias (i.e. `struct U256 { limbs: [u64; 4] }`)
+    pub fn concrete_definition(&self, span: Span) -> syn::Result<DeriveInput> {
+        let mut item = self.definition.item.clone();
+        let Some(aliased) = &self.alias else { return Ok(item) };
+
+        let mut substitution = Substitution::new(&item.generics, &aliased.args, span)?;
+        substitution.visit_data_mut(&mut item.data);
+
+        let mut generics = aliased.alias.item.generics.clone();
+        if let Some(mut where_clause) = item.generics.where_clause.take() {
+            substitution.visit_where_clause_mut(&mut where_clause);
+            generics
+                .make_where_clause()
+                .predicates
+                .extend(where_clause.predicates);
+        }
+
+        item.ident = aliased.alias.item.ident.clone();
+        item.generics = generics;
+
+        Ok(item)
+    }
+
+    /// the alias of the new type of the definition (i.e. `pub type
+    /// U256Redefined = UintRedefined<256, 4>;`)
+    pub fn alias_tokens(&self, vis: &Visibility, name: &Ident, definition_new_type: &Path) -> syn::Result<TokenStream> {
+        let Some(aliased) = &self.alias else { return Ok(TokenStream::new()) };
+
+        let generics = &aliased.alias.item.generics;
+        let generic_idents = generics
+            .type_params()
+            .map(|param| param.ident.clone())
+            .chain(generics.const_params().map(|param| param.ident.clone()))
+            .collect::<Vec<_>>();
+        let args = aliased
+            .args
+            .iter()
+            .map(|arg| match arg {
+                GenericArgument::Type(ty) => parse_type_to_redefined(ty, &TypeMap::default(), &generic_idents).map(GenericArgument::Type),
+                arg => Ok(arg.clone()),
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+        let where_clause = &generics.where_clause;
+
+        Ok(quote! {
+            #[allow(type_alias_bounds)]
+            #vis type #name #generics = #definition_new_type<#(#args),*> #where_clause;
+        })
+    }
+}
+
+/// the generic arguments of the last segment of a path
+fn generic_args(path: &Path) -> Vec<GenericArgument> {
+    match path.segments.last().map(|segment| &segment.arguments) {
+        Some(PathArguments::AngleBracketed(args)) => args.args.iter().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// the target of an alias with the alias's generic parameters substituted
+fn substitute_type(ty: &Type, generics: &Generics, args: &[GenericArgument], span: Span) -> syn::Result<Type> {
+    let mut ty = ty.clone();
+    Substitution::new(generics, args, span)?.visit_type_mut(&mut ty);
+
+    Ok(ty)
+}
+
+/// replaces generic parameters with the arguments given to them
+struct Substitution(HashMap<String, GenericArgument>);
+
+impl Substitution {
+    /// pairs the parameters with the arguments, lifetimes with lifetimes and
+    /// types/consts in order, using the defaults of parameters without one
+    fn new(generics: &Generics, args: &[GenericArgument], span: Span) -> syn::Result<Self> {
+        let mut lifetimes = args
+            .iter()
+            .filter(|arg| matches!(arg, GenericArgument::Lifetime(_)));
+        let mut others = args
+            .iter()
+            .filter(|arg| !matches!(arg, GenericArgument::Lifetime(_)));
+
+        let mut substitution = HashMap::new();
+        for param in &generics.params {
+            let (ident, arg) = match param {
+                GenericParam::Lifetime(param) => (param.lifetime.ident.to_string(), lifetimes.next().cloned()),
+                GenericParam::Type(param) => {
+                    let default = param.default.clone().map(GenericArgument::Type);
+                    (param.ident.to_string(), others.next().cloned().or(default))
+                }
+                GenericParam::Const(param) => {
+                    let default = param.default.clone().map(GenericArgument::Const);
+                    (param.ident.to_string(), others.next().cloned().or(default))
+                }
+            };
+
+            let arg = arg.ok_or_else(|| syn::Error::new(span, format!("The type alias doesn't give a generic argument for `{ident}`")))?;
+            substitution.insert(ident, arg);
+        }
+
+        Ok(Self(substitution))
+    }
+
+    /// the argument of a generic parameter written as a single identifier
+    fn argument(&self, path: &Path) -> Option<&GenericArgument> {
+        path.get_ident()
+            .and_then(|ident| self.0.get(&ident.to_string()))
+    }
+}
+
+impl VisitMut for Substitution {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if let Type::Path(TypePath { qself: None, path }) = ty {
+            if let Some(GenericArgument::Type(arg)) = self.argument(path) {
+                *ty = arg.clone();
+                return;
+            }
+        }
+
+        visit_mut::visit_type_mut(self, ty);
+    }
+
+    fn visit_generic_argument_mut(&mut self, arg: &mut GenericArgument) {
+        // const parameters given to a type (i.e. `Foo<N>`) are parsed as types
+        if let GenericArgument::Type(Type::Path(TypePath { qself: None, path })) = arg {
+            if let Some(GenericArgument::Const(expr)) = self.argument(path) {
+                *arg = GenericArgument::Const(expr.clone());
+                return;
+            }
+        }
+
+        visit_mut::visit_generic_argument_mut(self, arg);
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        if let Expr::Path(expr_path) = expr {
+            match self.argument(&expr_path.path) {
+                Some(GenericArgument::Const(arg)) => {
+                    *expr = arg.clone();
+                    return;
+                }
+                // a const parameter given through a const parameter of the alias
+                Some(GenericArgument::Type(Type::Path(TypePath { qself: None, path }))) => {
+                    *expr = parse_quote!(#path);
+                    return;
+                }
+                _ => (),
+            }
+        }
+
+        visit_mut::visit_expr_mut(self, expr);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if let Some(GenericArgument::Lifetime(arg)) = self.0.get(&lifetime.ident.to_string()) {
+            *lifetime = arg.clone();
+        }
+    }
+}
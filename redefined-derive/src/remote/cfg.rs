@@ -0,0 +1,496 @@
+use std::sync::Mutex;
+
+use syn::{
+    ext::IdentExt,
+    parenthesized,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    token, AttrStyle, Attribute, Data, DeriveInput, Fields, Ident, Item, LitStr, Meta, Token,
+};
+
+/// a configuration option (i.e. `unix`, `("target_os", "linux")` for
+/// `target_os = "linux"`)
+pub type CfgOption = (String, Option<String>);
+
+/// the options of the target the crate being compiled is compiled for, the
+/// same for every package
+static TARGET_CFGS: Mutex<Option<Vec<CfgOption>>> = Mutex::new(None);
+
+/// evaluates the `#[cfg(..)]` and `#[cfg_attr(..)]` attributes of a remote
+/// package's items, with the features cargo enables for the package and the
+/// options of the target
+///
+/// the options set by the package's build script (`cargo:rustc-cfg=..`) aren't
+/// known, so they are never set
+#[derive(Debug, Clone, Default)]
+pub struct CfgContext {
+    pub features:    Vec<String>,
+    /// the options of the target (i.e. `unix`, `("target_os", "linux")`)
+    pub target_cfgs: Vec<CfgOption>,
+}
+
+impl CfgContext {
+    pub fn new(features: Vec<String>) -> Self {
+        Self { features, target_cfgs: target_cfgs() }
+    }
+
+    /// the features enabled by the `default` feature of a package's manifest
+    pub fn default_features(manifest: &toml::Table) -> Vec<String> {
+        let declared = manifest
+            .get("features")
+            .and_then(|features| features.as_table());
+
+        let mut features = Vec::new();
+        let mut pending = vec!["default".to_string()];
+        while let Some(feature) = pending.pop() {
+            // `dep:a` and `a/b` enable dependencies and their features, not features of the
+            // package
+            if feature.contains([':', '/']) || features.contains(&feature) {
+                continue;
+            }
+
+            if let Some(enabled) = declared
+                .and_then(|declared| declared.get(&feature))
+                .and_then(|enabled| enabled.as_array())
+            {
+                pending.extend(
+                    enabled
+                        .iter()
+                        .filter_map(|enabled| enabled.as_str())
+                        .map(str::to_string),
+                );
+            }
+            features.push(feature);
+        }
+
+        features
+    }
+
+    /// whether an item, field or variant with the attributes is compiled
+    pub fn is_enabled(&self, attrs: &[Attribute]) -> bool {
+        attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("cfg"))
+            .all(|attr| {
+                // predicates that can't be parsed are assumed to hold
+                attr.parse_args::<CfgPredicate>()
+                    .map_or(true, |predicate| self.evaluate(&predicate))
+            })
+    }
+
+    /// replaces `#[cfg_attr(<PREDICATE>, a, b)]` with `#[a] #[b]` if the
+    /// predicate holds, otherwise removes it
+    pub fn expand_cfg_attrs(&self, attrs: &mut Vec<Attribute>) {
+        let mut expanded = Vec::new();
+        let mut pending = std::mem::take(attrs);
+        pending.reverse();
+
+        while let Some(attr) = pending.pop() {
+            if !attr.path().is_ident("cfg_attr") {
+                expanded.push(attr);
+                continue;
+            }
+
+            let Ok(cfg_attr) = attr.parse_args::<CfgAttr>() else { continue };
+            if self.evaluate(&cfg_attr.predicate) {
+                // the attributes can be `cfg_attr`s themselves
+                pending.extend(cfg_attr.attrs.into_iter().rev().map(|meta| Attribute {
+                    pound_token: attr.pound_token,
+                    style: AttrStyle::Outer,
+                    bracket_token: attr.bracket_token,
+                    meta,
+                }));
+            }
+        }
+
+        *attrs = expanded;
+    }
+
+    /// expands the `#[cfg_attr(..)]` attributes of an item, returning whether
+    /// it's compiled
+    pub fn configure_item(&self, item: &mut Item) -> bool {
+        let attrs = match item {
+            Item::Mod(item) => &mut item.attrs,
+            Item::Use(item) => &mut item.attrs,
+            Item::Type(item) => &mut item.attrs,
+            Item::Struct(item) => &mut item.attrs,
+            Item::Enum(item) => &mut item.attrs,
+            Item::Union(item) => &mut item.attrs,
+            _ => return true,
+        };
+        self.expand_cfg_attrs(attrs);
+
+        self.is_enabled(attrs)
+    }
+
+    /// removes the fields and variants of a struct/enum/union that aren't
+    /// compiled, expanding the `#[cfg_attr(..)]` attributes of the rest
+    pub fn configure(&self, item: &mut DeriveInput) {
+        self.expand_cfg_attrs(&mut item.attrs);
+
+        match &mut item.data {
+            Data::Struct(data_struct) => self.configure_fields(&mut data_struct.fields),
+            Data::Enum(data_enum) => {
+                data_enum.variants = std::mem::take(&mut data_enum.variants)
+                    .into_iter()
+                    .filter_map(|mut variant| {
+                        self.expand_cfg_attrs(&mut variant.attrs);
+                        self.configure_fields(&mut variant.fields);
+                        self.is_enabled(&variant.attrs).then_some(variant)
+                    })
+                    .collect();
+            }
+            Data::Union(data_union) => {
+                data_union.fields.named = std::mem::take(&mut data_union.fields.named)
+                    .into_iter()
+                    .filter_map(|mut field| {
+                        self.expand_cfg_attrs(&mut field.attrs);
+                        self.is_enabled(&field.attrs).then_some(field)
+                    })
+                    .collect();
+            }
+        }
+    }
+
+    fn configure_fields(&self, fields: &mut Fields) {
+        let fields = match fields {
+            Fields::Named(fields_named) => &mut fields_named.named,
+            Fields::Unnamed(fields_unnamed) => &mut fields_unnamed.unnamed,
+            Fields::Unit => return,
+        };
+
+        *fields = std::mem::take(fields)
+            .into_iter()
+            .filter_map(|mut field| {
+                self.expand_cfg_attrs(&mut field.attrs);
+                self.is_enabled(&field.attrs).then_some(field)
+            })
+            .collect();
+    }
+
+    /// whether the target sets the option
+    fn has_cfg(&self, key: &str, value: Option<&str>) -> bool {
+        self.target_cfgs
+            .iter()
+            .any(|(cfg_key, cfg_value)| cfg_key == key && cfg_value.as_deref() == value)
+    }
+
+    fn evaluate(&self, predicate: &CfgPredicate) -> bool {
+        match predicate {
+            CfgPredicate::All(predicates) => predicates.iter().all(|predicate| self.evaluate(predicate)),
+            CfgPredicate::Any(predicates) => predicates.iter().any(|predicate| self.evaluate(predicate)),
+            CfgPredicate::Not(predicate) => !self.evaluate(predicate),
+            CfgPredicate::Option(name) => self.has_cfg(name, None),
+            CfgPredicate::KeyValue(key, value) if key == "feature" => self.features.contains(value),
+            CfgPredicate::KeyValue(key, value) => self.has_cfg(key, Some(value)),
+        }
+    }
+}
+
+/// parses a configuration option as printed by `rustc --print cfg` (i.e.
+/// `unix`, `target_os="linux"`)
+fn parse_cfg(line: &str) -> CfgOption {
+    match line.split_once('=') {
+        Some((key, value)) => (key.to_string(), Some(value.trim_matches('"').to_string())),
+        None => (line.to_string(), None),
+    }
+}
+
+/// the value of an argument of the compiler expanding the macro (i.e.
+/// `--target`, `--out-dir`), the macro runs in the compiler's process so these
+/// are the arguments cargo compiles the crate with
+fn rustc_arg_in(args: &[String], name: &str) -> Option<String> {
+    let prefix = format!("{name}=");
+    args.iter()
+        .enumerate()
+        .find_map(|(idx, arg)| match arg.strip_prefix(&prefix) {
+            Some(value) => Some(value.to_string()),
+            None if arg == name => args.get(idx + 1).cloned(),
+            None => None,
+        })
+}
+
+/// the arguments of the compiler that change the options of the target
+/// (`--target` and the `-C` codegen options i.e. `target-feature=+avx2` or
+/// `opt-level=3`)
+fn target_args(args: &[String]) -> Vec<String> {
+    const CODEGEN_OPTIONS: [&str; 6] = ["target-feature=", "target-cpu=", "opt-level=", "debug-assertions", "overflow-checks", "panic="];
+
+    let mut target_args = Vec::new();
+    if let Some(target) = rustc_arg_in(args, "--target") {
+        target_args.extend(["--target".to_string(), target]);
+    }
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let option = match arg.strip_prefix("-C") {
+            Some("") => args.next().map(String::as_str),
+            option => option,
+        };
+        if let Some(option) = option.filter(|option| {
+            CODEGEN_OPTIONS
+                .iter()
+                .any(|codegen| option.starts_with(codegen))
+        }) {
+            target_args.extend(["-C".to_string(), option.to_string()]);
+        }
+    }
+
+    target_args
+}
+
+/// the options of the target the crate being compiled is compiled for, from
+/// `rustc --print cfg` with it's target and codegen options (i.e.
+/// `target_feature="sse2"`, `debug_assertions`)
+///
+/// options only set for the crate being compiled (i.e. `test`, `miri` or
+/// `--cfg ..`) aren't set for the remote package. The host's options are used
+/// if `rustc` can't be run
+fn target_cfgs() -> Vec<CfgOption> {
+    let mut cached = TARGET_CFGS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(target_cfgs) = cached.as_ref() {
+        return target_cfgs.clone();
+    }
+
+    let output = std::process::Command::new(std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string()))
+        .args(["--print", "cfg"])
+        .args(target_args(&std::env::args().collect::<Vec<_>>()))
+        .output()
+        .ok()
+        .filter(|output| output.status.success());
+    let target_cfgs = match output {
+        Some(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(parse_cfg)
+            .collect(),
+        None => host_cfgs(),
+    };
+    *cached = Some(target_cfgs.clone());
+
+    target_cfgs
+}
+
+/// the main options of the host the macro runs on
+fn host_cfgs() -> Vec<CfgOption> {
+    let mut cfgs = vec![
+        ("target_os".to_string(), Some(std::env::consts::OS.to_string())),
+        ("target_arch".to_string(), Some(std::env::consts::ARCH.to_string())),
+        ("target_family".to_string(), Some(std::env::consts::FAMILY.to_string())),
+        ("target_pointer_width".to_string(), Some(usize::BITS.to_string())),
+    ];
+    if !std::env::consts::FAMILY.is_empty() {
+        cfgs.push((std::env::consts::FAMILY.to_string(), None));
+    }
+    if cfg!(debug_assertions) {
+        cfgs.push(("debug_assertions".to_string(), None));
+    }
+
+    cfgs
+}
+
+/// the predicate of a `#[cfg(..)]` (i.e. `all(feature = "std", not(windows))`)
+#[derive(Debug, Clone)]
+enum CfgPredicate {
+    All(Vec<CfgPredicate>),
+    Any(Vec<CfgPredicate>),
+    Not(Box<CfgPredicate>),
+    /// `unix`
+    Option(String),
+    /// `feature = "std"`
+    KeyValue(String, String),
+}
+
+impl Parse for CfgPredicate {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident = input.call(Ident::parse_any)?;
+
+        if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            let value: LitStr = input.parse()?;
+            return Ok(Self::KeyValue(ident.to_string(), value.value()));
+        }
+
+        if !input.peek(token::Paren) {
+            return Ok(Self::Option(ident.to_string()));
+        }
+
+        let content;
+        parenthesized!(content in input);
+        let predicates = Punctuated::<Self, Token![,]>::parse_terminated(&content)?
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        match ident.to_string().as_str() {
+            "all" => Ok(Self::All(predicates)),
+            "any" => Ok(Self::Any(predicates)),
+            "not" => match <[_; 1]>::try_from(predicates) {
+                Ok([predicate]) => Ok(Self::Not(Box::new(predicate))),
+                Err(_) => Err(syn::Error::new_spanned(ident, "Expected a single predicate in `not(..)`")),
+            },
+            _ => Err(syn::Error::new_spanned(ident, "Expected `all`, `any` or `not`")),
+        }
+    }
+}
+
+/// `#[cfg_attr(<PREDICATE>, a, b)]`
+struct CfgAttr {
+    predicate: CfgPredicate,
+    attrs:     Vec<Meta>,
+}
+
+impl Parse for CfgAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let predicate = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let attrs = Punctuated::<Meta, Token![,]>::parse_terminated(input)?
+            .into_iter()
+            .collect();
+
+        Ok(Self { predicate, attrs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::parse_quote;
+
+    use super::*;
+
+    fn context() -> CfgContext {
+        CfgContext {
+            features:    vec!["std".to_string()],
+            target_cfgs: ["unix", "target_os=\"linux\"", "target_feature=\"sse2\""]
+                .into_iter()
+                .map(parse_cfg)
+                .collect(),
+        }
+    }
+
+    fn evaluate(predicate: &str) -> bool {
+        context().evaluate(&syn::parse_str(predicate).unwrap())
+    }
+
+    #[test]
+    fn test_predicates() {
+        assert!(evaluate("unix"));
+        assert!(!evaluate("windows"));
+        assert!(evaluate("target_os = \"linux\""));
+        assert!(evaluate("target_feature = \"sse2\""));
+        // set by build scripts
+        assert!(!evaluate("has_i128"));
+        assert!(!evaluate("test"));
+        assert!(!evaluate("miri"));
+        assert!(evaluate("all(feature = \"std\", not(windows))"));
+        assert!(!evaluate("all(feature = \"std\", feature = \"alloc\")"));
+        assert!(evaluate("any(windows, feature = \"std\")"));
+        assert!(evaluate("all()"));
+        assert!(!evaluate("any()"));
+    }
+
+    #[test]
+    fn test_invalid_predicates() {
+        assert!(syn::parse_str::<CfgPredicate>("not(unix, windows)").is_err());
+        assert!(syn::parse_str::<CfgPredicate>("one_of(unix)").is_err());
+
+        // predicates that can't be parsed are assumed to hold
+        assert!(context().is_enabled(&[parse_quote!(#[cfg(one_of(unix))])]));
+    }
+
+    #[test]
+    fn test_is_enabled() {
+        let context = context();
+
+        assert!(context.is_enabled(&[parse_quote!(#[doc = "docs"]), parse_quote!(#[cfg(unix)])]));
+        assert!(!context.is_enabled(&[parse_quote!(#[cfg(unix)]), parse_quote!(#[cfg(windows)])]));
+    }
+
+    #[test]
+    fn test_expand_cfg_attrs() {
+        let mut attrs: Vec<Attribute> = vec![
+            parse_quote!(#[doc = "docs"]),
+            parse_quote!(#[cfg_attr(feature = "std", derive(Debug), cfg_attr(unix, repr(C)))]),
+            parse_quote!(#[cfg_attr(windows, derive(Clone))]),
+            parse_quote!(#[cfg_attr(all(), cfg(windows))]),
+        ];
+        context().expand_cfg_attrs(&mut attrs);
+
+        let expected: Vec<Attribute> =
+            vec![parse_quote!(#[doc = "docs"]), parse_quote!(#[derive(Debug)]), parse_quote!(#[repr(C)]), parse_quote!(#[cfg(windows)])];
+        assert_eq!(attrs, expected);
+        assert!(!context().is_enabled(&attrs));
+    }
+
+    #[test]
+    fn test_configure() {
+        let mut item: DeriveInput = parse_quote! {
+            struct Foo {
+                a: u64,
+                #[cfg(windows)]
+                b: u64,
+                #[cfg_attr(feature = "std", doc = "c")]
+                c: u64,
+                #[cfg(windows)]
+                d: u64,
+            }
+        };
+        context().configure(&mut item);
+
+        let expected: DeriveInput = parse_quote! {
+            struct Foo {
+                a: u64,
+                #[doc = "c"]
+                c: u64
+            }
+        };
+        assert_eq!(item, expected);
+    }
+
+    #[test]
+    fn test_default_features() {
+        let manifest: toml::Table = toml::from_str(
+            r#"
+            [features]
+            default = ["std"]
+            std = ["alloc", "dep:serde", "serde/std"]
+            alloc = []
+            unused = []
+            "#,
+        )
+        .unwrap();
+
+        let mut features = CfgContext::default_features(&manifest);
+        features.sort();
+        assert_eq!(features, ["alloc", "default", "std"]);
+
+        assert_eq!(CfgContext::default_features(&toml::Table::new()), ["default"]);
+    }
+
+    #[test]
+    fn test_parse_cfg() {
+        assert_eq!(parse_cfg("unix"), ("unix".to_string(), None));
+        assert_eq!(parse_cfg("target_os=\"linux\""), ("target_os".to_string(), Some("linux".to_string())));
+    }
+
+    #[test]
+    fn test_target_args() {
+        let args = [
+            "rustc",
+            "--crate-name",
+            "foo",
+            "--target",
+            "aarch64-unknown-linux-gnu",
+            "-C",
+            "opt-level=3",
+            "-Ctarget-feature=+neon",
+            "-C",
+            "metadata=0123",
+            "--out-dir=/target/aarch64-unknown-linux-gnu/release/deps",
+        ]
+        .map(str::to_string);
+
+        assert_eq!(rustc_arg_in(&args, "--target").as_deref(), Some("aarch64-unknown-linux-gnu"));
+        assert_eq!(rustc_arg_in(&args, "--out-dir").as_deref(), Some("/target/aarch64-unknown-linux-gnu/release/deps"));
+        assert_eq!(rustc_arg_in(&args, "--edition"), None);
+        assert_eq!(target_args(&args), ["--target", "aarch64-unknown-linux-gnu", "-C", "opt-level=3", "-C", "target-feature=+neon"]);
+    }
+}
@@ -1,123 +1,253 @@
-use super::types::StructOrEnum;
+use std::path::PathBuf;
+
+use syn::{Attribute, Data, DataEnum, DataStruct, DataUnion, DeriveInput, Fields, Ident, Item, ItemType, Visibility};
+
+use super::cfg::CfgContext;
 
 /// represents a parsed remote type
 #[derive(Debug, Clone)]
 pub struct ParsedRemoteType {
-    /// the url or path
-    pub url_or_path: String,
-    /// the text of the struct/enum
-    pub type_text:   String,
-    /// where the parsed type is a struct or an enum
-    pub kind:        StructOrEnum,
+    /// the path of the file the type is defined in
+    pub path:         PathBuf,
+    /// the package the type is defined in, with it's exact version (i.e.
+    /// `alloy-primitives@=0.7.0`)
+    pub package_name: String,
+    /// the name of the crate the type is defined in (i.e. `ruint`)
+    pub crate_name:   String,
+    /// the modules the type is defined in, from the crate's root (i.e.
+    /// `["algorithms"]` for `ruint::algorithms::Foo`)
+    pub module_path:  Vec<String>,
+    /// the definition of the struct/enum/union
+    pub item:         DeriveInput,
 }
 
-impl ParsedRemoteType {
-    /// parse the text of a webpage
-    pub fn parse_from_page(url: String, page_contents: String, type_searched: &str) -> Option<Self> {
-        let mut lines = page_contents.lines();
+/// represents a parsed remote type alias (i.e. `pub type U256 = Uint<256, 4>;`)
+#[derive(Debug, Clone)]
+pub struct ParsedRemoteAlias {
+    /// the path of the file the alias is defined in
+    pub path:         PathBuf,
+    /// the package the alias is defined in, with it's exact version
+    pub package_name: String,
+    /// the name of the crate the alias is defined in (i.e. `alloy_primitives`)
+    pub crate_name:   String,
+    /// the modules the alias is defined in, from the crate's root
+    pub module_path:  Vec<String>,
+    /// the alias, without it's attributes
+    pub item:         ItemType,
+}
 
-        if let Some(first_line) = lines.find(|line| line_conditions(&line, type_searched)) {
-            let mut struct_lines = first_line.to_string();
+/// a remote type or type alias found by name
+#[derive(Debug, Clone)]
+pub enum RemoteItem {
+    Type(ParsedRemoteType),
+    Alias(ParsedRemoteAlias),
+}
 
-            let start_char = first_line_start_char(&struct_lines);
-            let kind = StructOrEnum::new_from_line(&struct_lines);
+impl RemoteItem {
+    pub fn ident(&self) -> &Ident {
+        match self {
+            RemoteItem::Type(remote_type) => &remote_type.item.ident,
+            RemoteItem::Alias(alias) => &alias.item.ident,
+        }
+    }
 
-            let mut closing_delimeter = '}';
-            if matches!(kind, StructOrEnum::Struct) {
-                let struct_kind = StructTypeHelper::new_from_line(&struct_lines);
+    /// the package the item is defined in
+    pub fn package_name(&self) -> &str {
+        match self {
+            RemoteItem::Type(remote_type) => &remote_type.package_name,
+            RemoteItem::Alias(alias) => &alias.package_name,
+        }
+    }
 
-                if let Some(delimeter) = struct_kind.closing_delimiter() {
-                    closing_delimeter = delimeter;
-                } else {
-                    return Some(Self { url_or_path: url, type_text: struct_lines, kind });
-                }
-            }
+    /// the name of the crate the item is defined in
+    pub fn crate_name(&self) -> &str {
+        match self {
+            RemoteItem::Type(remote_type) => &remote_type.crate_name,
+            RemoteItem::Alias(alias) => &alias.crate_name,
+        }
+    }
 
-            while let Some(line) = lines.next() {
-                if let Some(char) = line.chars().nth(start_char) {
-                    if char == closing_delimeter {
-                        struct_lines.push('\n');
-                        struct_lines.push_str(line);
-                        break;
-                    }
-                }
+    /// the modules the item is defined in, from the crate's root
+    pub fn module_path(&self) -> &[String] {
+        match self {
+            RemoteItem::Type(remote_type) => &remote_type.module_path,
+            RemoteItem::Alias(alias) => &alias.module_path,
+        }
+    }
 
-                struct_lines.push('\n');
-                struct_lines.push_str(line);
-            }
+    /// whether the item is declared `pub`
+    pub fn is_pub(&self) -> bool {
+        let vis = match self {
+            RemoteItem::Type(remote_type) => &remote_type.item.vis,
+            RemoteItem::Alias(alias) => &alias.item.vis,
+        };
+        matches!(vis, Visibility::Public(_))
+    }
 
-            return Some(Self { url_or_path: url, type_text: struct_lines, kind });
+    /// the path of the file the item is defined in
+    pub fn file_path(&self) -> &PathBuf {
+        match self {
+            RemoteItem::Type(remote_type) => &remote_type.path,
+            RemoteItem::Alias(alias) => &alias.path,
         }
+    }
 
-        None
+    /// the full path of the item (i.e. `ruint::algorithms::Foo`)
+    pub fn item_path(&self) -> String {
+        match self {
+            RemoteItem::Type(remote_type) => remote_type.item_path(),
+            RemoteItem::Alias(alias) => alias.item_path(),
+        }
     }
 }
 
-/// conditions whether a line is valid as the start of the target struct/enum
-fn line_conditions(line: &str, type_searched: &str) -> bool {
-    let is_struct = line.trim_start().starts_with("struct ")
-        || line.trim_start().starts_with("pub struct ")
-        || line.trim_start().starts_with("pub(crate) struct ");
-
-    let is_enum =
-        line.trim_start().starts_with("enum ") || line.trim_start().starts_with("pub enum ") || line.trim_start().starts_with("pub(crate) enum ");
-
-    // visibility options
-    let visibility = is_struct || is_enum;
+/// the full path of an item (i.e. `ruint::algorithms::Foo`)
+fn item_path(crate_name: &str, module_path: &[String], ident: &Ident) -> String {
+    std::iter::once(crate_name.to_string())
+        .chain(module_path.iter().cloned())
+        .chain(std::iter::once(ident.to_string()))
+        .collect::<Vec<_>>()
+        .join("::")
+}
 
-    // without generics
-    let case0 = visibility && (line.contains(&format!("enum {} ", type_searched)) || line.contains(&format!("struct {} ", type_searched)));
+impl ParsedRemoteType {
+    /// parses a source file, finding the struct/enum/union named
+    /// `type_searched` in it or it's inline modules
+    ///
+    /// files that aren't valid rust (i.e. templates) are skipped, as are items
+    /// disabled by `#[cfg(..)]`
+    pub fn parse_from_file(
+        path: PathBuf,
+        package_name: &str,
+        crate_name: &str,
+        file_contents: &str,
+        type_searched: &str,
+        cfg: &CfgContext,
+    ) -> Vec<Self> {
+        // only files mentioning the type are parsed
+        if !file_contents.contains(type_searched) {
+            return Vec::new();
+        }
 
-    // with generics
-    let case1 = visibility && (line.contains(&format!("enum {}<", type_searched)) || line.contains(&format!("struct {}<", type_searched)));
+        let Ok(file) = syn::parse_file(file_contents) else { return Vec::new() };
+        if !cfg.is_enabled(&file.attrs) {
+            return Vec::new();
+        }
 
-    // with paran
-    let case2 = visibility && (line.contains(&format!("enum {}(", type_searched)) || line.contains(&format!("struct {}(", type_searched)));
+        let mut found = Vec::new();
+        find_items(file.items, type_searched, cfg, &mut Vec::new(), &mut found);
+
+        found
+            .into_iter()
+            .map(|(module_path, item)| Self {
+                path: path.clone(),
+                package_name: package_name.to_string(),
+                crate_name: crate_name.to_string(),
+                module_path,
+                item,
+            })
+            .collect()
+    }
 
-    case0 || case1 || case2
+    /// the full path of the type (i.e. `ruint::algorithms::Foo`)
+    pub fn item_path(&self) -> String {
+        item_path(&self.crate_name, &self.module_path, &self.item.ident)
+    }
 }
 
-/// finds the index of the first character of the struct/enum
-fn first_line_start_char(line: &str) -> usize {
-    line.chars()
-        .enumerate()
-        .find(|(_, c)| *c != ' ')
-        .expect(&format!("Failed to parse the beginning of the first line of the type: {line}"))
-        .0
+impl ParsedRemoteAlias {
+    /// the full path of the alias (i.e. `ruint::aliases::U256`)
+    pub fn item_path(&self) -> String {
+        item_path(&self.crate_name, &self.module_path, &self.item.ident)
+    }
 }
 
-/// helper to determine how to format the struct
-enum StructTypeHelper {
-    /// struct A {
-    ///     ...
-    /// }
-    Bracketed,
-    /// struct A (
-    ///     ...
-    /// );
-    MultiLinesBraced,
-    /// struct A(...);
-    SingleLineBraced,
-}
+/// finds the structs/enums/unions named `type_searched` in the items, including
+/// inline modules, with the path of the inline modules they are in
+fn find_items(items: Vec<Item>, type_searched: &str, cfg: &CfgContext, module_path: &mut Vec<String>, found: &mut Vec<(Vec<String>, DeriveInput)>) {
+    for mut item in items {
+        if !cfg.configure_item(&mut item) {
+            continue;
+        }
 
-impl StructTypeHelper {
-    fn new_from_line(line: &str) -> Self {
-        if line.contains("{") {
-            StructTypeHelper::Bracketed
-        } else if line.contains("(") && !line.contains(")") && !line.contains(";") {
-            StructTypeHelper::MultiLinesBraced
-        } else if line.contains("(") && line.contains(")") && line.contains(";") {
-            StructTypeHelper::SingleLineBraced
-        } else {
-            panic!("Unable to get struct kind for line: {}", line)
+        match item {
+            Item::Mod(item_mod) => {
+                if let Some((_, items)) = item_mod.content {
+                    module_path.push(item_mod.ident.to_string());
+                    find_items(items, type_searched, cfg, module_path, found);
+                    module_path.pop();
+                }
+            }
+            item => {
+                if let Some(derive_input) = derive_input(item, cfg).filter(|derive_input| derive_input.ident == type_searched) {
+                    found.push((module_path.clone(), derive_input));
+                }
+            }
         }
     }
+}
 
-    fn closing_delimiter(&self) -> Option<char> {
-        match self {
-            StructTypeHelper::Bracketed => Some('}'),
-            StructTypeHelper::MultiLinesBraced => Some(')'),
-            StructTypeHelper::SingleLineBraced => None,
-        }
+/// the struct/enum/union of an item, without the attributes of the remote crate
+/// and the fields/variants disabled by `#[cfg(..)]`
+pub fn derive_input(item: Item, cfg: &CfgContext) -> Option<DeriveInput> {
+    let mut derive_input = match item {
+        Item::Struct(item) => DeriveInput {
+            attrs:    item.attrs,
+            vis:      item.vis,
+            ident:    item.ident,
+            generics: item.generics,
+            data:     Data::Struct(DataStruct { struct_token: item.struct_token, fields: item.fields, semi_token: item.semi_token }),
+        },
+        Item::Enum(item) => DeriveInput {
+            attrs:    item.attrs,
+            vis:      item.vis,
+            ident:    item.ident,
+            generics: item.generics,
+            data:     Data::Enum(DataEnum { enum_token: item.enum_token, brace_token: item.brace_token, variants: item.variants }),
+        },
+        Item::Union(item) => DeriveInput {
+            attrs:    item.attrs,
+            vis:      item.vis,
+            ident:    item.ident,
+            generics: item.generics,
+            data:     Data::Union(DataUnion { union_token: item.union_token, fields: item.fields }),
+        },
+        _ => return None,
+    };
+
+    cfg.configure(&mut derive_input);
+
+    Some(strip_remote_attributes(derive_input))
+}
+
+/// removes the attributes of the remote crate (i.e. `#[derive(..)]`,
+/// `#[serde(..)]`), keeping docs, `#[repr(..)]` and `#[default]`
+fn strip_remote_attributes(mut item: DeriveInput) -> DeriveInput {
+    let keep = |attr: &Attribute| {
+        ["doc", "repr", "default"]
+            .iter()
+            .any(|keep| attr.path().is_ident(keep))
+    };
+
+    item.attrs.retain(|attr| keep(attr));
+    let strip_fields = |fields: &mut Fields| {
+        fields
+            .iter_mut()
+            .for_each(|field| field.attrs.retain(|attr| keep(attr)))
+    };
+
+    match &mut item.data {
+        Data::Struct(data_struct) => strip_fields(&mut data_struct.fields),
+        Data::Enum(data_enum) => data_enum.variants.iter_mut().for_each(|variant| {
+            variant.attrs.retain(|attr| keep(attr));
+            strip_fields(&mut variant.fields);
+        }),
+        Data::Union(data_union) => data_union
+            .fields
+            .named
+            .iter_mut()
+            .for_each(|field| field.attrs.retain(|attr| keep(attr))),
     }
+
+    item
 }
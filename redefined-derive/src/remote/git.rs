@@ -0,0 +1,231 @@
+use std::path::{Path, PathBuf};
+
+use super::types::{cargo_home_dir, read_dirs};
+
+/// a git source of `Cargo.lock` (i.e.
+/// `git+https://gitlab.com/a/b?branch=main#<COMMIT>`), from any host
+#[derive(Debug, Clone)]
+pub struct GitSource {
+    /// the url of the repository, without the `?branch=..`, `?tag=..` or
+    /// `?rev=..` it's locked from
+    pub url:    String,
+    /// the locked commit
+    pub commit: String,
+}
+
+impl GitSource {
+    /// `None` if the source isn't a git source
+    pub fn parse(source: &str) -> Option<Self> {
+        let (url, commit) = source.strip_prefix("git+")?.rsplit_once('#')?;
+        let url = url.split_once('?').map_or(url, |(url, _)| url);
+
+        Some(Self { url: url.to_string(), commit: commit.to_string() })
+    }
+
+    /// the checkout of the locked commit in the cargo home
+    /// (`git/checkouts/<NAME>-<HASH>/<SHORT COMMIT>`)
+    ///
+    /// the checkouts of the repository are found by the hash of it's url, with
+    /// the hasher of current and older cargo versions, then by it's name
+    pub fn checkout(&self) -> Option<PathBuf> {
+        self.checkout_from(&cargo_home_dir().ok()?.join("git/checkouts"))
+    }
+
+    /// the checkout of the locked commit in a `git/checkouts` directory
+    fn checkout_from(&self, checkouts_dir: &Path) -> Option<PathBuf> {
+        let canonical_url = canonical_url(&self.url);
+
+        // the last segment of the url's path
+        let path = canonical_url
+            .split_once("://")
+            .map_or(canonical_url.as_str(), |(_, rest)| rest.split_once('/').map_or("", |(_, path)| path));
+        let name = path
+            .rsplit('/')
+            .next()
+            .filter(|name| !name.is_empty())
+            .unwrap_or("_empty");
+        let hashed_dirs = [short_hash(&canonical_url), legacy_short_hash(&canonical_url)].map(|hash| checkouts_dir.join(format!("{name}-{hash}")));
+        let named_dirs = read_dirs(checkouts_dir).into_iter().filter(|dir| {
+            dir.file_name()
+                .and_then(|dir_name| dir_name.to_str())
+                .and_then(|dir_name| dir_name.strip_prefix(&format!("{name}-")))
+                .is_some_and(|hash| hash.len() == 16 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+        });
+
+        hashed_dirs
+            .into_iter()
+            .chain(named_dirs)
+            .find_map(|repo_dir| self.checkout_in(&repo_dir))
+    }
+
+    /// the checkout of the locked commit among the checkouts of a repository,
+    /// which are named by the shortest unique prefix of their commit (at least
+    /// 7 characters)
+    fn checkout_in(&self, repo_dir: &Path) -> Option<PathBuf> {
+        read_dirs(repo_dir).into_iter().find(|dir| {
+            dir.file_name()
+                .and_then(|short_commit| short_commit.to_str())
+                .is_some_and(|short_commit| short_commit.len() >= 7 && self.commit.starts_with(short_commit))
+        })
+    }
+}
+
+/// the url cargo hashes for the directories of a repository, github urls are
+/// lowercased and a trailing `/` or `.git` is removed (i.e.
+/// `https://github.com/foo/bar` for `ssh://github.com/Foo/Bar.git`)
+fn canonical_url(url: &str) -> String {
+    let mut url = url.strip_suffix('/').unwrap_or(url).to_string();
+
+    if let Some((_, rest)) = url.split_once("://") {
+        let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+        let host = authority.rsplit('@').next().unwrap_or(authority);
+        let host = host.split(':').next().unwrap_or(host);
+        if host.eq_ignore_ascii_case("github.com") {
+            url = format!("https://{authority}{}", path.to_lowercase());
+        }
+    }
+
+    if let Some(stripped) = url.strip_suffix(".git") {
+        url = stripped.to_string();
+    }
+
+    url
+}
+
+/// the hash cargo names the directories of a repository by, the 64 bit
+/// `StableSipHasher128` hash of the url as hex
+fn short_hash(url: &str) -> String {
+    let data = [url.as_bytes(), &[0xff]].concat();
+    let (h0, h1) = sip13_128(&data);
+
+    to_hex(h0.wrapping_mul(3).wrapping_add(h1))
+}
+
+/// the hash of cargo versions before 1.85, the `SipHasher` hash of the url
+#[allow(deprecated)]
+fn legacy_short_hash(url: &str) -> String {
+    use std::hash::{Hash, Hasher, SipHasher};
+
+    let mut hasher = SipHasher::new_with_keys(0, 0);
+    url.hash(&mut hasher);
+
+    to_hex(hasher.finish())
+}
+
+fn to_hex(hash: u64) -> String {
+    hash.to_le_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// the 128 bit SipHash-1-3 of data with zero keys
+fn sip13_128(data: &[u8]) -> (u64, u64) {
+    let mut v = [0x736f6d6570736575_u64, 0x646f72616e646f6d ^ 0xee, 0x6c7967656e657261, 0x7465646279746573];
+
+    let sip_round = |v: &mut [u64; 4]| {
+        v[0] = v[0].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(13) ^ v[0];
+        v[0] = v[0].rotate_left(32);
+        v[2] = v[2].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(16) ^ v[2];
+        v[0] = v[0].wrapping_add(v[3]);
+        v[3] = v[3].rotate_left(21) ^ v[0];
+        v[2] = v[2].wrapping_add(v[1]);
+        v[1] = v[1].rotate_left(17) ^ v[2];
+        v[2] = v[2].rotate_left(32);
+    };
+
+    // the last word is padded with zeros and ends with the length
+    let chunks = data.chunks_exact(8);
+    let mut last = [0; 8];
+    last[..chunks.remainder().len()].copy_from_slice(chunks.remainder());
+    last[7] = data.len() as u8;
+    let words = chunks
+        .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+        .chain(std::iter::once(u64::from_le_bytes(last)));
+
+    for word in words {
+        v[3] ^= word;
+        sip_round(&mut v);
+        v[0] ^= word;
+    }
+
+    v[2] ^= 0xee;
+    (0..3).for_each(|_| sip_round(&mut v));
+    let h0 = v[0] ^ v[1] ^ v[2] ^ v[3];
+    v[1] ^= 0xdd;
+    (0..3).for_each(|_| sip_round(&mut v));
+    let h1 = v[0] ^ v[1] ^ v[2] ^ v[3];
+
+    (h0, h1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::remote::types::test_dir;
+
+    #[test]
+    fn test_parse() {
+        let git_source = GitSource::parse("git+https://github.com/SorellaLabs/redefined?branch=remote#0123456789abcdef").unwrap();
+        assert_eq!(git_source.url, "https://github.com/SorellaLabs/redefined");
+        assert_eq!(git_source.commit, "0123456789abcdef");
+
+        let git_source = GitSource::parse("git+ssh://git@gitlab.com/a/b.git#0123456789abcdef").unwrap();
+        assert_eq!(git_source.url, "ssh://git@gitlab.com/a/b.git");
+
+        assert!(GitSource::parse("registry+https://github.com/rust-lang/crates.io-index").is_none());
+        assert!(GitSource::parse("git+https://github.com/SorellaLabs/redefined").is_none());
+    }
+
+    #[test]
+    fn test_canonical_url() {
+        assert_eq!(canonical_url("ssh://github.com/Foo/Bar.git"), "https://github.com/foo/bar");
+        assert_eq!(canonical_url("https://github.com/SorellaLabs/redefined/"), "https://github.com/sorellalabs/redefined");
+        // only github urls are lowercased
+        assert_eq!(canonical_url("https://gitlab.com/Foo/Bar.git"), "https://gitlab.com/Foo/Bar");
+    }
+
+    #[test]
+    fn test_short_hash() {
+        // `~/.cargo/git/db/redefined-bf12648e020f076c`, the repository of
+        // `redefined-test-types`
+        assert_eq!(short_hash(&canonical_url("https://github.com/SorellaLabs/redefined")), "bf12648e020f076c");
+        assert_eq!(legacy_short_hash("https://github.com/sorellalabs/redefined").len(), 16);
+    }
+
+    #[test]
+    fn test_checkout_in() {
+        let repo_dir = test_dir("git-checkout");
+        std::fs::create_dir_all(repo_dir.join("0123456")).unwrap();
+        std::fs::create_dir_all(repo_dir.join("abc")).unwrap();
+
+        let checkout = |commit: &str| GitSource { url: String::new(), commit: commit.to_string() }.checkout_in(&repo_dir);
+        assert_eq!(checkout("0123456789abcdef"), Some(repo_dir.join("0123456")));
+        // shorter than 7 characters
+        assert_eq!(checkout("abcdef0123456789"), None);
+
+        std::fs::remove_dir_all(&repo_dir).unwrap();
+    }
+
+    #[test]
+    fn test_checkout_from() {
+        let checkouts_dir = test_dir("git-checkouts");
+        let checkout_dir = checkouts_dir.join("redefined-bf12648e020f076c/0123456");
+        std::fs::create_dir_all(&checkout_dir).unwrap();
+        // a checkout of another repository with the same name
+        std::fs::create_dir_all(checkouts_dir.join("redefined-0000000000000000/abcdef0")).unwrap();
+
+        let checkout = |url: &str, commit: &str| GitSource { url: url.to_string(), commit: commit.to_string() }.checkout_from(&checkouts_dir);
+        assert_eq!(checkout("ssh://git@github.com/SorellaLabs/redefined.git", "0123456789abcdef"), Some(checkout_dir));
+        // found by it's name when the hash doesn't match
+        assert_eq!(
+            checkout("https://gitlab.com/SorellaLabs/redefined", "abcdef0123456789"),
+            Some(checkouts_dir.join("redefined-0000000000000000/abcdef0"))
+        );
+        assert_eq!(checkout("https://github.com/SorellaLabs/redefined", "fedcba9876543210"), None);
+
+        std::fs::remove_dir_all(&checkouts_dir).unwrap();
+    }
+}
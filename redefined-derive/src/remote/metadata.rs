@@ -0,0 +1,146 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use serde::Deserialize;
+
+use super::types::{cargo, workspace_dir};
+
+/// the last loaded metadata, reloaded when the workspace's `Cargo.toml` or
+/// `Cargo.lock` are modified
+static CACHED_METADATA: Mutex<Option<CachedMetadata>> = Mutex::new(None);
+
+/// the metadata of a workspace, with the modification times of it's
+/// `Cargo.toml` and `Cargo.lock`
+type CachedMetadata = (PathBuf, [Option<SystemTime>; 2], Arc<CargoMetadata>);
+
+/// the output of `cargo metadata --format-version 1`
+#[derive(Debug, Clone, Deserialize)]
+pub struct CargoMetadata {
+    pub packages:         Vec<MetadataPackage>,
+    /// the resolved dependency graph, `None` with `--no-deps`
+    pub resolve:          Option<MetadataResolve>,
+    pub target_directory: PathBuf,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetadataPackage {
+    /// the id of the package in the dependency graph
+    pub id:            String,
+    pub name:          String,
+    pub version:       String,
+    /// the path of the package's `Cargo.toml`, for any source (registries,
+    /// git, path dependencies and workspace members)
+    pub manifest_path: PathBuf,
+    /// the source of the package as in `Cargo.lock` (i.e.
+    /// `registry+https://github.com/rust-lang/crates.io-index`), `None` for
+    /// path dependencies and workspace members
+    pub source:        Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetadataResolve {
+    pub nodes: Vec<MetadataNode>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetadataNode {
+    pub id:       String,
+    /// the features of the package enabled by cargo's feature resolution
+    pub features: Vec<String>,
+    pub deps:     Vec<MetadataDep>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MetadataDep {
+    /// the name the dependency is used with in the package (i.e.
+    /// `alloy_primitives`, or the name of a renamed dependency)
+    pub name: String,
+    /// the id of the dependency's package
+    pub pkg:  String,
+}
+
+impl CargoMetadata {
+    /// the metadata of the workspace, `None` if cargo can't resolve it offline
+    pub fn load() -> Option<Arc<Self>> {
+        let workspace_dir = workspace_dir().ok()?;
+        let modified = [workspace_dir.join("Cargo.toml"), workspace_dir.join("Cargo.lock")].map(|path| modified(&path));
+
+        let mut cached = CACHED_METADATA.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((cached_dir, cached_modified, metadata)) = cached.as_ref() {
+            if *cached_dir == workspace_dir && *cached_modified == modified {
+                return Some(metadata.clone());
+            }
+        }
+
+        let output = std::process::Command::new(cargo())
+            .arg("metadata")
+            .arg("--format-version=1")
+            .arg("--offline")
+            .arg("--manifest-path")
+            .arg(workspace_dir.join("Cargo.toml"))
+            .output()
+            .ok()
+            .filter(|output| output.status.success())?;
+        let metadata = Arc::new(serde_json::from_slice::<Self>(&output.stdout).ok()?);
+        *cached = Some((workspace_dir, modified, metadata.clone()));
+
+        Some(metadata)
+    }
+
+    /// the package of the manifest in `manifest_dir` (i.e. the crate being
+    /// compiled)
+    pub fn package_in(&self, manifest_dir: &Path) -> Option<&MetadataPackage> {
+        let manifest_path = manifest_dir.join("Cargo.toml");
+
+        self.packages
+            .iter()
+            .find(|package| package.manifest_path == manifest_path)
+    }
+
+    /// the packages a package depends on by the name they are used with (i.e.
+    /// `alloy_primitives` -> `alloy-primitives 0.7.0`)
+    pub fn dependencies(&self, package_id: &str) -> HashMap<String, &MetadataPackage> {
+        let Some(node) = self
+            .resolve
+            .iter()
+            .flat_map(|resolve| resolve.nodes.iter())
+            .find(|node| node.id == package_id)
+        else {
+            return HashMap::new();
+        };
+
+        node.deps
+            .iter()
+            .filter_map(|dep| {
+                let package = self.packages.iter().find(|package| package.id == dep.pkg)?;
+                Some((dep.name.clone(), package))
+            })
+            .collect()
+    }
+
+    /// the enabled features of a package, `None` if it isn't in the dependency
+    /// graph
+    pub fn features(&self, package_name: &str, version: &str) -> Option<Vec<String>> {
+        let package = self
+            .packages
+            .iter()
+            .find(|package| package.name == package_name && package.version == version)?;
+
+        self.resolve
+            .as_ref()?
+            .nodes
+            .iter()
+            .find(|node| node.id == package.id)
+            .map(|node| node.features.clone())
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
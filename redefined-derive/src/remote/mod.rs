@@ -1,141 +1,383 @@
+mod alias;
+mod cfg;
 mod file_parser;
+mod git;
+mod metadata;
+mod module_tree;
 mod package;
+mod recursive;
+mod resolver;
+mod sources;
 mod types;
 
-use proc_macro2::{Ident, TokenStream};
+use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
-use syn::{bracketed, parenthesized, parse::Parse, spanned::Spanned, DeriveInput, LitStr, Token};
-
-use self::package::Package;
-use crate::derive;
+use syn::{
+    braced, bracketed, parenthesized,
+    parse::{Parse, Parser},
+    parse_quote, Attribute, Data, DeriveInput, LitStr, Path, Token, Visibility,
+};
+
+use self::{
+    alias::RemoteDefinition,
+    package::{Package, SearchTarget},
+    recursive::Recursion,
+    resolver::Resolver,
+};
+use crate::{
+    attributes::{
+        symbol::{CONCRETE, CRATE, MODULE, NEW_TYPE_SYMBOLS, REMOTE_SYMBOLS},
+        type_attr::TypeAttribute,
+        type_map::TypeMap,
+        ContainerAttributes,
+    },
+    config::Config,
+    derive,
+    new_types::{module_tokens, nested_visibility, parse_attributes},
+    outer::explicit_crate_path,
+    registry::{new_type_name, new_type_path, record_remote_type},
+};
 
 pub fn expand_redefined_remote(input: TokenStream) -> syn::Result<TokenStream> {
     let parsed: RemoteTypes = syn::parse2(input)?;
 
-    parsed.execute()
+    let tokens = parsed.execute()?;
+    let config_tokens = Config::load()?.track_tokens();
+
+    Ok(quote!(#tokens #config_tokens))
 }
 
 #[derive(Debug, Clone)]
 pub struct RemoteType {
-    pub package: Package,
-    pub derives: Vec<Ident>,
-    pub other_attrs: TokenStream,
-    pub transmute: bool,
-    pub no_impl: bool,
+    pub package:        Package,
+    pub derives:        Vec<Path>,
+    pub other_attrs:    TokenStream,
+    /// `#[redefined_attr(..)]` attributes only used for new types (i.e.
+    /// `map_types(..)`, `vis = ".."`), applied to the remote type
+    pub new_type_attrs: TokenStream,
+    pub transmute:      bool,
+    pub no_impl:        bool,
+    /// whether type aliases generate a concrete new type (i.e. `struct
+    /// U256Redefined { limbs: [u64; 4] }`) instead of the generic new type of
+    /// the aliased type and an alias of it
+    pub concrete:       bool,
+    /// mirrors the remote types used in the fields of the remote types, from
+    /// `#[redefined_attr(recursive)]`
+    pub recursion:      Option<Recursion>,
+    /// the targets of the package searched besides it's library, from
+    /// `#[redefined_attr(search(examples, tests))]`
+    pub search:         Vec<SearchTarget>,
+    /// the module the new types are generated in, from `redefined_remote!(mod
+    /// <MODULE> { .. })`
+    pub module:         Option<RemoteModule>,
 }
 
 impl RemoteType {
     /// runs the remote type execution
     /// added for future use in fields of structs
-    pub fn execute(&self, name: &Ident) -> syn::Result<TokenStream> {
-        let remote_type_text = self
-            .package
-            .fetch_from_file_cache(&name.to_string())
-            .type_text;
+    ///
+    /// `generated` are the paths of the definitions already generated by this
+    /// macro, so a type and it's aliases generate it's new type once
+    pub fn execute(&self, name: &RemoteName, remote_definition: RemoteDefinition, generated: &mut Vec<String>) -> syn::Result<TokenStream> {
+        let definition_path = remote_definition.definition.item_path();
+
+        let Some(aliased) = &remote_definition.alias else {
+            if generated.contains(&definition_path) {
+                return Ok(TokenStream::new());
+            }
+            generated.push(definition_path);
 
-        let tokens = self.parse_remote_type_text(&remote_type_text, name);
+            return self.parse_remote_type(remote_definition.definition.item, name);
+        };
 
-        tokens
-    }
+        if self.concrete {
+            let concrete_definition = remote_definition.concrete_definition(name.ident.span())?;
+            return self.parse_remote_type(concrete_definition, name);
+        }
 
-    /// parses the remote type into tokens
-    fn parse_remote_type_text(&self, remote_type_text: &str, name: &Ident) -> syn::Result<TokenStream> {
-        //let remote_type_text = remote_type_text.replace("\\", "");
+        // the generic new type of the aliased type, with it's path as the source type
+        let definition = &remote_definition.definition.item;
+        let definition_name = RemoteName { path: syn::parse_str(&definition_path)?, ident: definition.ident.clone(), rename: None };
+        record_remote_type(&[path_segments(&definition_path)], None, self.module.as_ref().map(|module| &module.ident));
+        let definition_new_type = new_type_path(&definition_name.path)?;
+        let definition_tokens = if generated.contains(&definition_path) {
+            TokenStream::new()
+        } else {
+            generated.push(definition_path);
+            self.parse_remote_type(definition.clone(), &definition_name)?
+        };
 
-        let remote_type_name = name.to_string();
-        let (other_attr, derives) = (&self.other_attrs, &self.derives);
-        let tokens = if self.no_impl {
-            let struct_def: DeriveInput = syn::parse_str(&remote_type_text)?;
-            let redefined_struct_def = derive::expand_derive_redefined(&struct_def, true).unwrap_or_else(syn::Error::into_compile_error);
+        let alias_name = match &name.rename {
+            Some(rename) => rename.clone(),
+            None => new_type_name(&aliased.alias.item.ident)?,
+        };
+        let alias_vis = match self.module {
+            Some(_) => nested_visibility(&aliased.alias.item.vis),
+            None => aliased.alias.item.vis.clone(),
+        };
+        let alias_tokens = remote_definition.alias_tokens(&alias_vis, &alias_name, &definition_new_type)?;
 
-            let mod_redefined_struct_def = redefined_struct_def
-                .to_string()
-                .replace("#[derive(Redefined)]", "")
-                .replace(&format!("#[redefined({})]", remote_type_name), "");
+        Ok(quote! {
+            #definition_tokens
+            #alias_tokens
+        })
+    }
 
-            let final_struct_def: DeriveInput = syn::parse_str(&mod_redefined_struct_def)?;
+    /// the `map_types(..)` of the new types, these types aren't mirrored
+    /// recursively
+    fn type_map(&self) -> syn::Result<TypeMap> {
+        let attrs = Attribute::parse_outer.parse2(self.new_type_attrs.clone())?;
+        Ok(parse_attributes(&attrs, Span::call_site())?.type_map)
+    }
+
+    /// generates the new type of the remote type, with it's impls unless
+    /// `no_impl`
+    fn parse_remote_type(&self, remote_type: DeriveInput, name: &RemoteName) -> syn::Result<TokenStream> {
+        let struct_def = self.parse_remote_struct_def(remote_type, name)?;
+        let final_struct_def = self.parse_final_struct_def(&struct_def)?;
 
+        let (other_attr, derives) = (&self.other_attrs, &self.derives);
+        let remote_type = &name.path;
+        let tokens = if self.no_impl {
             let mut derives = derives.clone();
-            derives.retain(|d| d.to_string() != "Redefined");
+            derives.retain(|d| d.segments.last().is_none_or(|seg| seg.ident != "Redefined"));
 
             quote! {
                 #[derive(#(#derives),*)]
                 #other_attr
                 #final_struct_def
             }
+        } else if self.transmute {
+            quote! {
+                #[derive(#(#derives),*)]
+                #[redefined(#remote_type)]
+                #[redefined_attr(transmute)]
+                #other_attr
+                #final_struct_def
+            }
         } else {
-            // let remote_type_text = remote_type_text
-            //     .replace(&format!("struct {}", remote_type_name), &format!("struct
-            // {}Redefined", remote_type_name))     .replace(&format!("enum {}",
-            // remote_type_name), &format!("enum {}Redefined", remote_type_name));
+            quote! {
+                #[derive(#(#derives),*)]
+                #[redefined(#remote_type)]
+                #other_attr
+                #final_struct_def
+            }
+        };
 
-            let struct_def: DeriveInput = syn::parse_str(&remote_type_text)?;
-            let redefined_struct_def = derive::expand_derive_redefined(&struct_def, true).unwrap_or_else(syn::Error::into_compile_error);
+        Ok(tokens)
+    }
 
-            //panic!("DEF: \n{:?}", redefined_struct_def.to_string());
+    /// the new type of the remote type without it's derives and source type,
+    /// keeping the visibilities from the calling module if it's generated in a
+    /// module
+    fn parse_final_struct_def(&self, struct_def: &DeriveInput) -> syn::Result<DeriveInput> {
+        let redefined_struct_def = derive::expand_derive_redefined(struct_def, true)?;
+
+        let mut final_struct_def: DeriveInput = syn::parse2(redefined_struct_def)?;
+        final_struct_def
+            .attrs
+            .retain(|attr| !attr.path().is_ident("derive") && !attr.path().is_ident("redefined"));
+
+        if self.module.is_some() {
+            final_struct_def.vis = nested_visibility(&final_struct_def.vis);
+            if let Data::Struct(data_struct) = &mut final_struct_def.data {
+                data_struct
+                    .fields
+                    .iter_mut()
+                    .for_each(|field| field.vis = nested_visibility(&field.vis));
+            }
+        }
 
-            let mod_redefined_struct_def = redefined_struct_def
-                .to_string()
-                .replace("#[derive(Redefined)]", "")
-                .replace(&format!("#[redefined({})]", remote_type_name), "");
+        Ok(final_struct_def)
+    }
 
-            let final_struct_def: DeriveInput = syn::parse_str(&mod_redefined_struct_def)?;
+    /// adds the new type attributes and the name of the new type to the remote
+    /// type
+    fn parse_remote_struct_def(&self, mut struct_def: DeriveInput, name: &RemoteName) -> syn::Result<DeriveInput> {
+        struct_def
+            .attrs
+            .extend(Attribute::parse_outer.parse2(self.new_type_attrs.clone())?);
+
+        if let Some(rename) = &name.rename {
+            let rename = rename.to_string();
+            struct_def
+                .attrs
+                .push(parse_quote!(#[redefined_attr(name = #rename)]));
+        }
 
-            //panic!("DEF: \n{}", final_struct_def.to_token_stream().to_string());
+        Ok(struct_def)
+    }
+}
 
-            // let struct_def: DeriveInput = syn::parse_str(&remote_type_text)?;
+/// the path of a remote type, and the name of it's new type if renamed (i.e.
+/// `Uint as U256Mirror`, `ruint::algorithms::Foo`)
+#[derive(Debug, Clone)]
+pub struct RemoteName {
+    /// the path as written, used as the source type
+    pub path:   Path,
+    /// the last segment of the path
+    pub ident:  Ident,
+    pub rename: Option<Ident>,
+}
 
-            let remote_type = Ident::new(&remote_type_name, struct_def.span());
+impl RemoteName {
+    /// the modules of the path, without the leading crate name (i.e.
+    /// `["algorithms"]` for `ruint::algorithms::Foo`)
+    ///
+    /// a path starting at the crate's root is matched exactly, otherwise the
+    /// modules are matched as the end of the type's module path
+    pub fn modules(&self, crate_name: &str) -> ModulePath {
+        let mut modules = self
+            .path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>();
+        modules.pop();
 
-            if self.transmute {
-                quote! {
+        match modules.first() {
+            Some(first) if first == crate_name => ModulePath::Root(modules.split_off(1)),
+            _ => ModulePath::Suffix(modules),
+        }
+    }
 
-                    #[derive(#(#derives),*)]
-                    #[redefined(#remote_type)]
-                    #[redefined_attr(transmute)]
-                    #other_attr
-                    #final_struct_def
-                }
-            } else {
-                quote! {
-                    #[derive(#(#derives),*)]
-                    #[redefined(#remote_type)]
-                    #other_attr
-                    #final_struct_def
-                }
-            }
-        };
+    /// the path as written (i.e. `ruint::algorithms::Foo`)
+    pub fn path_string(&self) -> String {
+        self.path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+}
+
+/// the modules of a remote type's path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModulePath {
+    /// from the crate's root (i.e. `ruint::algorithms::Foo`)
+    Root(Vec<String>),
+    /// the end of the module path (i.e. `algorithms::Foo`, `Foo`)
+    Suffix(Vec<String>),
+}
 
-        //panic!("DEF: \n{}", tokens.to_string());
+impl ModulePath {
+    pub fn segments(&self) -> &[String] {
+        match self {
+            ModulePath::Root(modules) | ModulePath::Suffix(modules) => modules,
+        }
+    }
+}
 
-        Ok(tokens)
+impl Parse for RemoteName {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let path = input.call(Path::parse_mod_style)?;
+        let ident = path
+            .segments
+            .last()
+            .map(|segment| segment.ident.clone())
+            .ok_or_else(|| syn::Error::new(input.span(), "Expected the path of a remote type"))?;
+
+        let rename = if input.peek(Token![as]) {
+            input.parse::<Token![as]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+
+        Ok(Self { path, ident, rename })
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct RemoteTypes {
-    pub names: Vec<Ident>,
+    pub names:       Vec<RemoteName>,
     pub remote_type: RemoteType,
 }
 
+/// the module remote types are generated in (i.e. `pub mod mirror { .. }`)
+#[derive(Debug, Clone)]
+pub struct RemoteModule {
+    pub vis:   Visibility,
+    pub ident: Ident,
+}
+
 impl RemoteTypes {
     /// runs the remote type execution
     /// added for future use in fields of structs
     pub fn execute(&self) -> syn::Result<TokenStream> {
-        let tokens = self
+        let remote_type = &self.remote_type;
+        let mut resolver = Resolver::default();
+        let mut definitions = self
             .names
             .iter()
-            .map(|name| self.remote_type.execute(name))
+            .map(|name| {
+                Ok((
+                    name.clone(),
+                    remote_type
+                        .package
+                        .fetch_from_file_cache(name, &mut resolver, &remote_type.search)?,
+                ))
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        if let Some(recursion) = &remote_type.recursion {
+            recursion.add_nested_types(&mut resolver, &mut definitions, &remote_type.type_map()?)?;
+        }
+
+        // recorded first, so the new types can reference each other in any order, by
+        // the path they're named by and the path of their definition
+        for (name, definition) in &definitions {
+            let item_path = path_segments(&definition.item_path());
+            let mut written_path = path_segments(&name.path_string());
+            if let Some(crate_name) = item_path
+                .first()
+                .filter(|crate_name| written_path.first() != Some(crate_name))
+            {
+                written_path.insert(0, crate_name.clone());
+            }
+
+            let module = remote_type.module.as_ref().map(|module| &module.ident);
+            record_remote_type(&[written_path, item_path], name.rename.as_ref(), module);
+        }
+
+        let mut generated = Vec::new();
+        let tokens = definitions
+            .into_iter()
+            .map(|(name, definition)| remote_type.execute(&name, definition, &mut generated))
             .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(quote!( #(#tokens)*))
+        match &self.remote_type.module {
+            Some(module) => Ok(module_tokens(&module.vis, &module.ident, quote!( #(#tokens)*))),
+            None => Ok(quote!( #(#tokens)*)),
+        }
     }
 }
 
 impl Parse for RemoteTypes {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
-        let mut derives = vec![Ident::new("Redefined", input.span())];
-        if input.peek(Token![#]) {
+        // `mod <MODULE> { .. }`
+        if input.peek(Token![mod]) || input.peek(Token![pub]) {
+            let vis: Visibility = input.parse()?;
+            input.parse::<Token![mod]>()?;
+            let ident: Ident = input.parse()?;
+
+            let module_content;
+            braced!(module_content in input);
+            let mut this: Self = module_content.parse()?;
+            if this.remote_type.module.is_some() {
+                return Err(syn::Error::new(ident.span(), "Remote types can't be generated in nested modules"));
+            }
+            this.remote_type.module = Some(RemoteModule { vis, ident });
+
+            return Ok(this);
+        }
+
+        let config = Config::load()?;
+
+        let mut derives = Vec::new();
+        // other attributes can come first (i.e. `#[redefined_attr(..)]`)
+        if input.peek(Token![#]) && is_derive_attr(input) {
             input.parse::<Token![#]>()?; // #
 
             let bracketed_derive;
@@ -153,8 +395,24 @@ impl Parse for RemoteTypes {
             );
         }
 
-        let mut other_attrs = Default::default();
+        // the default derives and attributes from the config
+        for derive in config.parse_derives()? {
+            if !derives.contains(&derive) {
+                derives.push(derive);
+            }
+        }
+        derives.retain(|derive| derive != "Redefined");
+
+        let config_attrs = config
+            .attributes
+            .iter()
+            .map(|attr| syn::parse_str::<TokenStream>(attr))
+            .collect::<syn::Result<Vec<_>>>()?;
+        let mut other_attrs = quote!(#(#config_attrs)*);
+        let mut new_type_attrs = TokenStream::new();
         let (mut from_source, mut to_source) = (false, false);
+        let mut crate_attrs = Vec::new();
+        let mut remote_attrs = Vec::new();
         while input.peek(Token![#]) {
             input.parse::<Token![#]>()?;
 
@@ -171,6 +429,43 @@ impl Parse for RemoteTypes {
                 from_source = true
             }
 
+            let symbols = redefined_attr_symbols(&attr)?;
+            crate_attrs.extend(
+                symbols
+                    .as_ref()
+                    .and_then(|symbols| CRATE.find_type_attr(symbols)),
+            );
+            // `#[redefined_attr(concrete)]`, `#[redefined_attr(recursive, ..)]` are only
+            // used by this macro
+            if let Some(symbols) = symbols.filter(|symbols| {
+                REMOTE_SYMBOLS
+                    .iter()
+                    .any(|symbol| symbol.contained_in(symbols))
+            }) {
+                if let Some(other) = symbols
+                    .iter()
+                    .find(|attr| !REMOTE_SYMBOLS.contains(&attr.symbol))
+                {
+                    let remote_symbols = REMOTE_SYMBOLS
+                        .map(|symbol| format!("`{symbol}`"))
+                        .join(", ");
+                    return Err(syn::Error::new_spanned(
+                        attr,
+                        format!("The `{}` attribute can't be in the same `#[redefined_attr(..)]` as {remote_symbols}", other.symbol),
+                    ));
+                }
+                remote_attrs.push((attr, symbols));
+                continue;
+            }
+
+            if is_new_type_attr(&attr)? {
+                new_type_attrs = quote! {
+                    #new_type_attrs
+                    #[ #attr ]
+                };
+                continue;
+            }
+
             other_attrs = quote! {
                 #other_attrs
                 #[ #attr ]
@@ -181,7 +476,7 @@ impl Parse for RemoteTypes {
         bracketed!(names_content in input);
 
         let names = names_content
-            .parse_terminated(Ident::parse, Token![,])?
+            .parse_terminated(RemoteName::parse, Token![,])?
             .into_iter()
             .collect::<Vec<_>>();
 
@@ -191,8 +486,7 @@ impl Parse for RemoteTypes {
             .parse()
             .map_err(|e| syn::Error::new(e.span(), "Failed to parse url of the remote type's crate/package"))?;
 
-        let package = Package::new(package_name.value())
-            .map_err(|_| syn::Error::new(package_name.span(), "Failed to parse the cargo lock for this package"))?;
+        let package = Package::new(package_name.value()).map_err(|e| syn::Error::new(package_name.span(), e))?;
 
         let mut no_impl = false;
         if input.peek(Token![:]) {
@@ -202,17 +496,75 @@ impl Parse for RemoteTypes {
                 .parse()
                 .map_err(|e| syn::Error::new(e.span(), "Failed to parse no_impl ident - MUST BE 'no_impl'"))?;
 
-            if no_impl_ident.to_string() == "no_impl" {
+            if no_impl_ident == "no_impl" {
                 no_impl = true
             }
         }
 
-        let remote_type = RemoteType { package, derives, other_attrs, no_impl, transmute: !(from_source && to_source) };
+        // `Redefined` is derived through the crate path if it's given
+        let redefined_derive = match explicit_crate_path(&crate_attrs)? {
+            Some(crate_path) => parse_quote!(#crate_path::Redefined),
+            None => Path::from(Ident::new("Redefined", input.span())),
+        };
+        let derives = std::iter::once(redefined_derive)
+            .chain(derives.into_iter().map(Path::from))
+            .collect();
+
+        let remote_type = RemoteType {
+            package,
+            derives,
+            other_attrs,
+            new_type_attrs,
+            no_impl,
+            transmute: config.remote.transmute && !(from_source && to_source),
+            concrete: remote_attrs
+                .iter()
+                .any(|(_, symbols)| CONCRETE.contained_in(symbols)),
+            recursion: Recursion::from_attrs(&remote_attrs)?,
+            search: SearchTarget::from_attrs(&remote_attrs)?,
+            module: None,
+        };
 
         let this = Self { names, remote_type };
 
-        //panic!("NO IMPL: \n{:?}", this);
-
         Ok(this)
     }
 }
+
+/// whether the next attribute is `#[derive(..)]`
+fn is_derive_attr(input: syn::parse::ParseStream) -> bool {
+    let fork = input.fork();
+    fork.parse::<Token![#]>().is_ok()
+        && (|| {
+            let bracketed_attr;
+            bracketed!(bracketed_attr in fork);
+            Ok::<_, syn::Error>(bracketed_attr.parse::<Ident>()? == "derive")
+        })()
+        .unwrap_or(false)
+}
+
+/// the parsed symbols of a `#[redefined_attr(..)]` attribute
+fn redefined_attr_symbols(attr: &TokenStream) -> syn::Result<Option<Vec<TypeAttribute>>> {
+    let attr: Attribute = parse_quote!(#[#attr]);
+    if !attr.path().is_ident("redefined_attr") {
+        return Ok(None);
+    }
+
+    Ok(Some(attr.parse_args_with(ContainerAttributes::parse)?.0))
+}
+
+/// whether an attribute is a `#[redefined_attr(..)]` with only new type
+/// attributes
+fn is_new_type_attr(attr: &TokenStream) -> syn::Result<bool> {
+    let Some(symbols) = redefined_attr_symbols(attr)? else { return Ok(false) };
+    if MODULE.contained_in(&symbols) {
+        return Err(syn::Error::new_spanned(attr, "Use `redefined_remote!(mod <MODULE> { .. })` to generate remote types in a module"));
+    }
+
+    Ok(!symbols.is_empty() && symbols.iter().all(|s| NEW_TYPE_SYMBOLS.contains(&s.symbol)))
+}
+
+/// the segments of a path (i.e. `["ruint", "Uint"]` for `ruint::Uint`)
+fn path_segments(path: &str) -> Vec<String> {
+    path.split("::").map(str::to_string).collect()
+}
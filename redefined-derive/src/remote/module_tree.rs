@@ -0,0 +1,337 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use syn::{Attribute, Expr, ExprLit, Item, Lit, Meta, UseTree, Visibility};
+
+use super::{
+    cfg::CfgContext,
+    file_parser::{derive_input, ParsedRemoteAlias, ParsedRemoteType, RemoteItem},
+    package::Package,
+    ModulePath,
+};
+
+/// the structs/enums/unions of a package, found by following the `mod`
+/// declarations of it's library target
+#[derive(Debug, Clone)]
+pub struct ModuleTree {
+    /// the package, with it's exact version (i.e. `ruint@=1.20.1`)
+    pub package_name: String,
+    /// the name of the library (i.e. `alloy_primitives`)
+    pub crate_name:   String,
+    pub types:        Vec<ParsedRemoteType>,
+    /// the type aliases of every module
+    pub aliases:      Vec<ParsedRemoteAlias>,
+    /// the path of every module, from the crate's root
+    pub modules:      Vec<Vec<String>>,
+    /// the modules that aren't declared `pub`
+    pub private_mods: Vec<Vec<String>>,
+    /// the `use` items of every module
+    pub uses:         Vec<UseItem>,
+    /// the packages of the dependencies by the name they are used with (i.e.
+    /// `alloy_primitives` -> `alloy-primitives@=0.7.0`, or `alloy-primitives`
+    /// without `cargo metadata`)
+    pub dependencies: HashMap<String, String>,
+    /// the items disabled by `#[cfg(..)]` aren't loaded
+    pub cfg:          CfgContext,
+}
+
+/// a name imported by a `use` item (i.e. `pub use ruint::Uint as U;`)
+#[derive(Debug, Clone)]
+pub struct UseItem {
+    /// the module of the `use` item
+    pub module_path: Vec<String>,
+    /// the imported name, `None` for a glob (`use a::*;`)
+    pub name:        Option<String>,
+    /// the path as written (i.e. `["ruint", "Uint"]`, or `["a"]` for a glob)
+    pub target:      Vec<String>,
+    /// whether the name is re-exported (`pub use`)
+    pub is_pub:      bool,
+}
+
+impl ModuleTree {
+    /// loads the module tree of the package in `package_dir`, or `None` if it
+    /// has no library target
+    pub fn load(package_dir: &Path, package: &Package, cfg: CfgContext) -> Option<Self> {
+        let manifest = manifest(package_dir);
+        let (crate_name, lib_path) = lib_target(&manifest, package_dir, &package.package_name)?;
+
+        let mut this = Self {
+            package_name: package.spec(),
+            crate_name,
+            types: Vec::new(),
+            aliases: Vec::new(),
+            modules: vec![Vec::new()],
+            private_mods: Vec::new(),
+            uses: Vec::new(),
+            dependencies: package
+                .dependencies()
+                .unwrap_or_else(|| dependencies(&manifest)),
+            cfg,
+        };
+        let mut loaded_files = Vec::new();
+        this.load_file(&lib_path, lib_path.parent()?.to_path_buf(), Vec::new(), &mut loaded_files);
+
+        Some(this)
+    }
+
+    /// the types and type aliases named `type_searched` in the given modules
+    pub fn find(&self, modules: &ModulePath, type_searched: &str) -> Vec<RemoteItem> {
+        let in_modules = |module_path: &Vec<String>| match modules {
+            ModulePath::Root(modules) => module_path == modules,
+            ModulePath::Suffix(modules) => module_path.ends_with(modules),
+        };
+
+        let types = self
+            .types
+            .iter()
+            .filter(|remote_type| remote_type.item.ident == type_searched && in_modules(&remote_type.module_path))
+            .cloned()
+            .map(RemoteItem::Type);
+        let aliases = self
+            .aliases
+            .iter()
+            .filter(|alias| alias.item.ident == type_searched && in_modules(&alias.module_path))
+            .cloned()
+            .map(RemoteItem::Alias);
+
+        types.chain(aliases).collect()
+    }
+
+    /// whether the module exists
+    pub fn has_module(&self, module_path: &[String]) -> bool {
+        self.modules.iter().any(|module| module == module_path)
+    }
+
+    /// whether the items of a module can be named from outside of the crate,
+    /// when it and all of it's parents are `pub`
+    pub fn is_public_module(&self, module_path: &[String]) -> bool {
+        !self
+            .private_mods
+            .iter()
+            .any(|private| module_path.starts_with(private))
+    }
+
+    /// whether a name is a module, type, alias or import in the module, rather
+    /// than an external crate
+    pub fn is_local(&self, module_path: &[String], name: &str) -> bool {
+        self.has_module(&[module_path, &[name.to_string()]].concat())
+            || !self
+                .find(&ModulePath::Root(module_path.to_vec()), name)
+                .is_empty()
+            || self
+                .uses_in(module_path, true)
+                .any(|use_item| use_item.name.as_deref() == Some(name))
+    }
+
+    /// the `use` items of a module, only it's re-exports (`pub use`) unless
+    /// `in_scope`
+    pub fn uses_in<'a>(&'a self, module_path: &'a [String], in_scope: bool) -> impl Iterator<Item = &'a UseItem> + 'a {
+        self.uses
+            .iter()
+            .filter(move |use_item| use_item.module_path == module_path && (in_scope || use_item.is_pub))
+    }
+
+    /// parses a file of the module at `module_path`, where `module_dir` is the
+    /// directory of it's child modules
+    fn load_file(&mut self, file_path: &Path, module_dir: PathBuf, module_path: Vec<String>, loaded_files: &mut Vec<PathBuf>) {
+        // modules included twice (i.e. with `#[path]`) are only loaded once
+        if loaded_files.iter().any(|loaded| loaded == file_path) {
+            return;
+        }
+        loaded_files.push(file_path.to_path_buf());
+
+        let Ok(file_contents) = std::fs::read_to_string(file_path) else { return };
+        let Ok(file) = syn::parse_file(&file_contents) else { return };
+        // `#![cfg(..)]`
+        if !self.cfg.is_enabled(&file.attrs) {
+            return;
+        }
+
+        let file_dir = file_path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let context = FileContext { file_path, file_dir };
+        self.load_items(file.items, &context, &module_dir, &module_path, false, loaded_files);
+    }
+
+    /// collects the types and re-exports of the items of a module, loading the
+    /// files of it's `mod a;` declarations
+    fn load_items(
+        &mut self,
+        items: Vec<Item>,
+        context: &FileContext,
+        module_dir: &Path,
+        module_path: &[String],
+        is_inline: bool,
+        loaded_files: &mut Vec<PathBuf>,
+    ) {
+        for mut item in items {
+            if !self.cfg.configure_item(&mut item) {
+                continue;
+            }
+
+            match item {
+                Item::Mod(item_mod) => {
+                    let mod_name = item_mod.ident.to_string();
+                    let mod_name = mod_name.trim_start_matches("r#");
+                    let mut child_path = module_path.to_vec();
+                    child_path.push(mod_name.to_string());
+                    self.modules.push(child_path.clone());
+                    if !matches!(item_mod.vis, Visibility::Public(_)) {
+                        self.private_mods.push(child_path.clone());
+                    }
+
+                    let path_attr = path_attribute(&item_mod.attrs);
+                    match item_mod.content {
+                        // `mod a { .. }`
+                        Some((_, items)) => {
+                            let child_dir = match &path_attr {
+                                Some(path) => module_dir.join(path),
+                                None => module_dir.join(mod_name),
+                            };
+                            self.load_items(items, context, &child_dir, &child_path, true, loaded_files);
+                        }
+                        // `mod a;`
+                        None => {
+                            if let Some(path) = path_attr {
+                                let base_dir = if is_inline { module_dir } else { &context.file_dir };
+                                let file_path = base_dir.join(path);
+                                // files included with `#[path]` are always treated as `mod.rs` files
+                                let child_dir = file_path.parent().unwrap_or(Path::new("")).to_path_buf();
+                                self.load_file(&file_path, child_dir, child_path, loaded_files);
+                            } else if module_dir.join(format!("{mod_name}.rs")).is_file() {
+                                self.load_file(&module_dir.join(format!("{mod_name}.rs")), module_dir.join(mod_name), child_path, loaded_files);
+                            } else {
+                                let file_path = module_dir.join(mod_name).join("mod.rs");
+                                self.load_file(&file_path, module_dir.join(mod_name), child_path, loaded_files);
+                            }
+                        }
+                    }
+                }
+                Item::Use(item_use) => {
+                    let is_pub = matches!(item_use.vis, Visibility::Public(_));
+                    flatten_use_tree(&item_use.tree, &mut Vec::new(), module_path, is_pub, &mut self.uses);
+                }
+                Item::Type(mut item_type) => {
+                    item_type.attrs.clear();
+                    self.aliases.push(ParsedRemoteAlias {
+                        path:         context.file_path.to_path_buf(),
+                        package_name: self.package_name.clone(),
+                        crate_name:   self.crate_name.clone(),
+                        module_path:  module_path.to_vec(),
+                        item:         item_type,
+                    });
+                }
+                item => {
+                    if let Some(item) = derive_input(item, &self.cfg) {
+                        self.types.push(ParsedRemoteType {
+                            path: context.file_path.to_path_buf(),
+                            package_name: self.package_name.clone(),
+                            crate_name: self.crate_name.clone(),
+                            module_path: module_path.to_vec(),
+                            item,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// the file the items are parsed from
+struct FileContext<'a> {
+    file_path: &'a Path,
+    /// the directory of the file, `#[path]` attributes outside of inline
+    /// modules are relative to it
+    file_dir:  PathBuf,
+}
+
+/// the parsed manifest of the package in `package_dir`, empty if it can't be
+/// read
+pub fn manifest(package_dir: &Path) -> toml::Table {
+    std::fs::read_to_string(package_dir.join("Cargo.toml"))
+        .ok()
+        .and_then(|manifest| manifest.parse::<toml::Table>().ok())
+        .unwrap_or_default()
+}
+
+/// the library name and the path of the root file of the package's library
+/// target, from the `[lib]` table of it's manifest or `src/lib.rs`
+fn lib_target(manifest: &toml::Table, package_dir: &Path, package_name: &str) -> Option<(String, PathBuf)> {
+    let lib = manifest.get("lib").and_then(|lib| lib.as_table());
+
+    let crate_name = lib
+        .and_then(|lib| lib.get("name"))
+        .and_then(|name| name.as_str())
+        .unwrap_or(package_name)
+        .replace('-', "_");
+    let lib_path = package_dir.join(
+        lib.and_then(|lib| lib.get("path"))
+            .and_then(|path| path.as_str())
+            .unwrap_or("src/lib.rs"),
+    );
+
+    lib_path.is_file().then_some((crate_name, lib_path))
+}
+
+/// the packages of the manifest's dependencies (including target specific
+/// ones) by the name they are used with, following renames (`package = ".."`)
+pub fn dependencies(manifest: &toml::Table) -> HashMap<String, String> {
+    let target_dependencies = manifest
+        .get("target")
+        .and_then(|target| target.as_table())
+        .into_iter()
+        .flat_map(|target| target.values())
+        .filter_map(|target| target.get("dependencies"));
+
+    manifest
+        .get("dependencies")
+        .into_iter()
+        .chain(target_dependencies)
+        .filter_map(|dependencies| dependencies.as_table())
+        .flat_map(|dependencies| dependencies.iter())
+        .map(|(name, dependency)| {
+            let package = dependency
+                .get("package")
+                .and_then(|package| package.as_str())
+                .unwrap_or(name);
+            (name.replace('-', "_"), package.to_string())
+        })
+        .collect()
+}
+
+/// adds the names imported by a `use` tree
+fn flatten_use_tree(tree: &UseTree, prefix: &mut Vec<String>, module_path: &[String], is_pub: bool, uses: &mut Vec<UseItem>) {
+    let mut push = |name: Option<String>, target: Vec<String>| {
+        uses.push(UseItem { module_path: module_path.to_vec(), name, target, is_pub });
+    };
+
+    match tree {
+        UseTree::Path(use_path) => {
+            prefix.push(use_path.ident.to_string());
+            flatten_use_tree(&use_path.tree, prefix, module_path, is_pub, uses);
+            prefix.pop();
+        }
+        // `a::{self}` exports `a`
+        UseTree::Name(use_name) if use_name.ident == "self" => push(prefix.last().cloned(), prefix.clone()),
+        UseTree::Name(use_name) => push(Some(use_name.ident.to_string()), [prefix.as_slice(), &[use_name.ident.to_string()]].concat()),
+        UseTree::Rename(use_rename) if use_rename.ident == "self" => push(Some(use_rename.rename.to_string()), prefix.clone()),
+        UseTree::Rename(use_rename) => push(Some(use_rename.rename.to_string()), [prefix.as_slice(), &[use_rename.ident.to_string()]].concat()),
+        UseTree::Glob(_) => push(None, prefix.clone()),
+        UseTree::Group(use_group) => use_group
+            .items
+            .iter()
+            .for_each(|tree| flatten_use_tree(tree, prefix, module_path, is_pub, uses)),
+    }
+}
+
+/// the value of a `#[path = ".."]` attribute
+fn path_attribute(attrs: &[Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| match &attr.meta {
+        Meta::NameValue(nv) if nv.path.is_ident("path") => match &nv.value {
+            Expr::Lit(ExprLit { lit: Lit::Str(path), .. }) => Some(path.value()),
+            _ => None,
+        },
+        _ => None,
+    })
+}
@@ -1,16 +1,29 @@
-use core::panic;
 use std::{
-    fs::DirEntry,
-    io::{BufRead, Read},
+    collections::HashMap,
+    path::{Path, PathBuf},
 };
 
+use proc_macro2::{Span, TokenStream};
+use semver::{Version, VersionReq};
+
 use super::{
-    file_parser::ParsedRemoteType,
-    types::{cargo_home_dir, get_all_files},
+    alias::{expand_alias, RemoteDefinition},
+    cfg::CfgContext,
+    file_parser::{ParsedRemoteType, RemoteItem},
+    git::GitSource,
+    metadata::{CargoMetadata, MetadataPackage},
+    module_tree::{dependencies, manifest, ModuleTree},
+    resolver::Resolver,
+    sources::{replaced_source, unpack_crate, vendored_package, ReplacedSource},
+    types::{cargo_home_dir, get_all_files, read_dirs},
+    RemoteName,
+};
+use crate::{
+    attributes::{symbol::SEARCH, type_attr::TypeAttribute},
+    remote::types::workspace_dir,
 };
-use crate::remote::types::workspace_dir;
 
-/// parsing of the package from crates-io OR github
+/// a locked package, and where it's sources are
 #[derive(Debug, Clone)]
 pub struct Package {
     pub package_name: String,
@@ -19,190 +32,530 @@ pub struct Package {
 }
 
 impl Package {
-    pub fn new(package: String) -> std::io::Result<Self> {
-        let root_path = workspace_dir();
-        let path = root_path.to_str().unwrap();
+    /// finds a locked package by it's name and an optional version requirement
+    /// (i.e. `ruint`, `ruint@1.12`), the name can also be the name a dependency
+    /// is renamed to in the crate being compiled (i.e. `primitives` for
+    /// `primitives = { package = "alloy-primitives" }`)
+    ///
+    /// packages are located with `cargo metadata`, or from `Cargo.lock` if it
+    /// can't resolve the workspace offline
+    pub fn new(spec: String) -> std::io::Result<Self> {
+        let (name, version_req) = match spec.split_once('@') {
+            Some((name, version_req)) => {
+                let version_req = VersionReq::parse(version_req)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Invalid version requirement in '{spec}': {e}")))?;
+                (name, Some(version_req))
+            }
+            None => (spec.as_str(), None),
+        };
 
-        let cargo_lock_file_path = format!("{path}/Cargo.lock");
+        match CargoMetadata::load() {
+            Some(metadata) => Self::from_metadata(&metadata, name, version_req.as_ref()),
+            None => Self::from_cargo_lock(name, version_req.as_ref()),
+        }
+    }
 
-        let file = std::fs::File::open(cargo_lock_file_path)?;
-        let reader = std::io::BufReader::new(file).lines();
+    /// the spec of this exact package (i.e. `ruint@=1.20.1`), the items of it's
+    /// module tree refer to the package with it
+    pub fn spec(&self) -> String {
+        format!("{}@={}", self.package_name, self.version)
+    }
 
-        let searching_for = format!("name = \"{package}\"");
+    /// the package from the metadata, preferring the dependency of the crate
+    /// being compiled when several versions are locked
+    fn from_metadata(metadata: &CargoMetadata, name: &str, version_req: Option<&VersionReq>) -> std::io::Result<Self> {
+        let dependencies = current_manifest_dir()
+            .and_then(|manifest_dir| metadata.package_in(&manifest_dir))
+            .map(|current| metadata.dependencies(&current.id))
+            .unwrap_or_default();
+        let dependency = dependencies
+            .iter()
+            .find(|(dep_name, package)| (**dep_name == name.replace('-', "_") || package.name == name) && matches(version_req, &package.version))
+            .map(|(_, package)| *package);
+
+        let package = match dependency {
+            Some(package) => package,
+            None => {
+                let candidates = metadata
+                    .packages
+                    .iter()
+                    .filter(|package| package.name == name && matches(version_req, &package.version))
+                    .collect::<Vec<_>>();
+                single_version(name, version_req, candidates, |package: &&MetadataPackage| package.version.clone())?
+            }
+        };
 
-        let mut version = None;
+        let kind = PackageKind::new(&package.name, package.source.clone(), || package.manifest_path.parent().map(Path::to_path_buf))?;
+        Ok(Package { package_name: package.name.clone(), version: package.version.clone(), kind })
+    }
 
-        let mut found = false;
-        for line_res in reader {
-            if let Ok(line) = line_res {
-                if line == searching_for {
-                    found = true;
-                }
+    /// the package from the `[[package]]`s of `Cargo.lock`, following the
+    /// renames of the crate being compiled's manifest
+    fn from_cargo_lock(name: &str, version_req: Option<&VersionReq>) -> std::io::Result<Self> {
+        let workspace_dir = workspace_dir().map_err(|e| std::io::Error::new(std::io::ErrorKind::NotFound, e.to_string()))?;
+        let cargo_lock = std::fs::read_to_string(workspace_dir.join("Cargo.lock"))?
+            .parse::<toml::Table>()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("Could not parse the cargo lock: {e}")))?;
+
+        let renames = current_manifest_dir()
+            .map(|manifest_dir| dependencies(&manifest(&manifest_dir)))
+            .unwrap_or_default();
+        let name = renames
+            .get(&name.replace('-', "_"))
+            .map(String::as_str)
+            .unwrap_or(name);
+
+        let candidates = cargo_lock
+            .get("package")
+            .and_then(|packages| packages.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|package| package.as_table())
+            .filter(|package| package.get("name").and_then(|n| n.as_str()) == Some(name))
+            .filter_map(|package| {
+                let version = package.get("version")?.as_str()?.to_string();
+                let source = package.get("source").and_then(|source| source.as_str());
+                matches(version_req, &version).then(|| (version, source.map(str::to_string)))
+            })
+            .collect::<Vec<_>>();
+        let (version, source) = single_version(name, version_req, candidates, |(version, _)| version.clone())?;
 
-                if found && line.starts_with("version = ") {
-                    version = Some(line.replace("version = ", ""));
-                }
+        let kind = PackageKind::new(name, source, || find_local_package(&workspace_dir, name))?;
 
-                if found && line.starts_with("source = ") {
-                    let kind = if line.contains("https://github.com/rust-lang/crates.io-index") {
-                        PackageKind::CratesIo(package.clone())
-                    } else {
-                        PackageKind::parse_github_repo(&line.replace("source = ", "").replace("git+", ""))
-                    };
-
-                    return Ok(Package {
-                        package_name: package.clone(),
-                        version: version
-                            .expect(&format!("could not parse 'version' for package '{package}' in cargo lock"))
-                            .trim_matches('\"')
-                            .to_string(),
-                        kind,
-                    });
-                }
-            }
-        }
+        Ok(Package { package_name: name.to_string(), version, kind })
+    }
+
+    /// the exact packages of the package's dependencies by the name they are
+    /// used with (i.e. `alloy_primitives` -> `alloy-primitives@=0.7.0`), `None`
+    /// if `cargo metadata` can't be loaded
+    pub fn dependencies(&self) -> Option<HashMap<String, String>> {
+        let metadata = CargoMetadata::load()?;
+        let package = metadata
+            .packages
+            .iter()
+            .find(|package| package.name == self.package_name && package.version == self.version)?;
+
+        let dependencies = metadata
+            .dependencies(&package.id)
+            .into_iter()
+            .map(|(name, dependency)| (name, format!("{}@={}", dependency.name, dependency.version)))
+            .collect();
 
-        panic!("Cound Not Parse Package: '{package}'")
+        Some(dependencies)
     }
 
-    /// attempts the fetch the type from the cached files of the repo
-    pub fn fetch_from_file_cache(&self, type_searched: &str) -> ParsedRemoteType {
-        let package_dir = self.kind.fetch_from_cargo(&self.version);
-        //panic!("PATH: {:?}", package_dir.path().as_os_str());
+    /// the directory of the package's manifest, inside the checkout of a
+    /// repository for git packages
+    pub fn package_dir(&self) -> Option<PathBuf> {
+        self.kind
+            .fetch_from_cargo(&self.package_name, &self.version)
+    }
 
-        let mut paths = Vec::new();
-        get_all_files(&package_dir, &mut paths);
+    /// the module tree of the package's library target, if it's sources are in
+    /// the cargo home
+    pub fn module_tree(&self) -> Option<ModuleTree> {
+        let package_dir = self.package_dir()?;
+        ModuleTree::load(&package_dir, self, self.cfg(&package_dir))
+    }
 
-        let results = paths
-            .into_iter()
-            .filter_map(|path| {
-                let mut file = std::fs::File::open(&path).expect(&format!("Could not open file {:?} from cargo file cache", &path));
-                let mut file_contents = String::new();
-                file.read_to_string(&mut file_contents)
-                    .expect(&format!("Could not read file {:?} to string", path));
-
-                let p = path.as_path().to_str().unwrap().to_string();
-                ParsedRemoteType::parse_from_page(p.clone(), file_contents, type_searched)
-            })
+    /// the context of the package's `#[cfg(..)]` attributes, the features cargo
+    /// enables for it (the default features if `cargo metadata` can't be
+    /// loaded)
+    pub fn cfg(&self, package_dir: &Path) -> CfgContext {
+        let features = CargoMetadata::load()
+            .and_then(|metadata| metadata.features(&self.package_name, &self.version))
+            .unwrap_or_else(|| CfgContext::default_features(&manifest(package_dir)));
+
+        CfgContext::new(features)
+    }
+
+    /// attempts the fetch the type from the cached files of the repo, following
+    /// the `mod` declarations of it's library target to match the modules of
+    /// the remote type's path, and type aliases to the type they alias
+    ///
+    /// the `search` targets (i.e. `examples/`) are searched file by file if the
+    /// library has no results
+    pub fn fetch_from_file_cache(&self, name: &RemoteName, resolver: &mut Resolver, search: &[SearchTarget]) -> syn::Result<RemoteDefinition> {
+        let type_searched = &name.ident;
+        let type_name = type_searched.to_string();
+        let package_dir = self.package_dir().ok_or_else(|| {
+            syn::Error::new(type_searched.span(), format!("Could not find the sources of package `{}` version `{}`", self.package_name, self.version))
+        })?;
+
+        // packages without a library target are searched file by file
+        let Some(module_tree) = resolver.tree(&self.spec()) else {
+            let results = self.fetch_from_files(&package_dir, package_files(&package_dir, search)?, &type_name)?;
+            let candidates = results
+                .iter()
+                .map(|result| result.path.display().to_string())
+                .collect::<Vec<_>>();
+            let definition = self.single_result(name, results, candidates)?;
+
+            return Ok(RemoteDefinition { definition, alias: None });
+        };
+
+        let modules = name.modules(&module_tree.crate_name);
+        let mut results = resolver.find(&self.spec(), &modules, &type_name);
+        if results.is_empty() && !search.is_empty() {
+            results = self
+                .fetch_from_files(&package_dir, target_files(&package_dir, search)?, &type_name)?
+                .into_iter()
+                .map(RemoteItem::Type)
+                .collect();
+        }
+        let candidates = results
+            .iter()
+            .map(|result| format!("`{}`", result.item_path()))
             .collect::<Vec<_>>();
 
-        if results.len() == 0 {
-            panic!("No Results From File Cache For Package: {:?}", self);
-        } else if results.len() > 1 {
-            panic!("Too Many Results From File Cache For Package: {:?}\nResults: {:?}", self, results);
-        } else {
-            results.first().unwrap().clone()
+        match self.single_result(name, results, candidates)? {
+            RemoteItem::Type(definition) => Ok(RemoteDefinition { definition, alias: None }),
+            RemoteItem::Alias(alias) => expand_alias(resolver, alias, type_searched.span()),
+        }
+    }
+
+    /// the only result of a search, erroring with the candidates if there are
+    /// several
+    fn single_result<T>(&self, name: &RemoteName, results: Vec<T>, candidates: Vec<String>) -> syn::Result<T> {
+        match <[_; 1]>::try_from(results) {
+            Ok([result]) => Ok(result),
+            Err(results) if results.is_empty() => Err(syn::Error::new(
+                name.ident.span(),
+                format!("No Results From File Cache For `{}` In Package `{}`", name.path_string(), self.package_name),
+            )),
+            Err(_) => Err(syn::Error::new(
+                name.ident.span(),
+                format!(
+                    "Too Many Results From File Cache For `{}` In Package `{}`, use the full path of the type: {}",
+                    name.path_string(),
+                    self.package_name,
+                    candidates.join(", ")
+                ),
+            )),
         }
     }
+
+    /// the types named `type_name` in the files of the package
+    fn fetch_from_files(&self, package_dir: &Path, paths: Vec<PathBuf>, type_name: &str) -> syn::Result<Vec<ParsedRemoteType>> {
+        let (spec, crate_name) = (self.spec(), self.package_name.replace('-', "_"));
+        let cfg = self.cfg(package_dir);
+
+        let mut results = Vec::new();
+        for path in paths {
+            let file_contents = std::fs::read_to_string(&path)
+                .map_err(|e| syn::Error::new(Span::call_site(), format!("Failed to read {} from the cargo file cache: {e}", path.display())))?;
+            results.extend(ParsedRemoteType::parse_from_file(path, &spec, &crate_name, &file_contents, type_name, &cfg));
+        }
+
+        Ok(results)
+    }
 }
 
+/// where the sources of a package are
 #[derive(Debug, Clone)]
 pub enum PackageKind {
-    CratesIo(String),
-    Github(String, String),
+    /// a package from crates-io or an alternative registry (i.e.
+    /// `registry+https://github.com/rust-lang/crates.io-index`)
+    Registry(String),
+    /// a package from a git repository, checked out in the cargo home
+    Git(GitSource),
+    /// the directory of the package's manifest, for path dependencies and
+    /// workspace members
+    Local(PathBuf),
 }
 
 impl PackageKind {
-    fn parse_github_repo(url: &str) -> Self {
-        let split_commit = url.split("#").collect::<Vec<_>>();
-        let commit = split_commit
-            .last()
-            .expect(&format!("Could not find github commit hash for package {:?}", url))
-            .to_string();
-
-        let mut split_owner = split_commit
-            .first()
-            .expect(&format!("Could not parse owner/repo for package {:?}", url))
-            .split("/")
-            .collect::<Vec<_>>();
+    /// the kind of a package by it's source in `Cargo.lock` or `cargo
+    /// metadata`, `package_dir` finds the directory of a package without one
+    /// (path dependencies and workspace members)
+    fn new(package_name: &str, source: Option<String>, package_dir: impl FnOnce() -> Option<PathBuf>) -> std::io::Result<Self> {
+        match source {
+            Some(source) if source.starts_with("registry+") || source.starts_with("sparse+") => Ok(PackageKind::Registry(source)),
+            Some(source) => match GitSource::parse(&source) {
+                Some(git_source) => Ok(PackageKind::Git(git_source)),
+                None => {
+                    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, format!("Unsupported source '{source}' for package '{package_name}'")))
+                }
+            },
+            None => {
+                let package_dir = package_dir().ok_or_else(|| {
+                    std::io::Error::new(std::io::ErrorKind::NotFound, format!("Could not find the path of package '{package_name}' in the workspace"))
+                })?;
+                Ok(PackageKind::Local(package_dir))
+            }
+        }
+    }
 
-        let repo_and_query = split_owner
-            .pop()
-            .expect(&format!("Could not parse repo/query for package {:?}", url))
-            .split("?")
-            .collect::<Vec<_>>();
+    fn fetch_from_cargo(&self, package_name: &str, version: &str) -> Option<PathBuf> {
+        match self {
+            PackageKind::Registry(registry) => Self::fetch_from_registry(registry, package_name, version),
+            // the repository can have several packages (i.e. a workspace)
+            PackageKind::Git(git_source) => find_local_package(&git_source.checkout()?, package_name),
+            PackageKind::Local(package_dir) => package_dir.is_dir().then(|| package_dir.clone()),
+        }
+    }
+
+    /// the sources of a registry package, from the source replacing the
+    /// registry (i.e. `cargo vendor`), it's unpacked sources in the cargo home,
+    /// or it's `.crate` archive in the cargo home unpacked to the target
+    /// directory
+    fn fetch_from_registry(registry: &str, package_name: &str, version: &str) -> Option<PathBuf> {
+        let dir_name = format!("{package_name}-{version}");
+        match replaced_source(registry) {
+            Some(ReplacedSource::Directory(vendor_dir)) => return vendored_package(&vendor_dir, package_name, version),
+            Some(ReplacedSource::LocalRegistry(registry_dir)) => return unpack_crate(&registry_dir.join(format!("{dir_name}.crate"))),
+            None => (),
+        }
+
+        // the directories of every index (i.e. `registry/src/index.crates.io-<HASH>`)
+        let registry_dir = cargo_home_dir().ok()?.join("registry");
+        let unpacked = read_dirs(&registry_dir.join("src"))
+            .into_iter()
+            .map(|index_dir| index_dir.join(&dir_name))
+            .find(|package_dir| package_dir.is_dir());
+
+        unpacked.or_else(|| {
+            read_dirs(&registry_dir.join("cache"))
+                .into_iter()
+                .map(|index_dir| index_dir.join(format!("{dir_name}.crate")))
+                .filter(|archive| archive.is_file())
+                .find_map(|archive| unpack_crate(&archive))
+        })
+    }
+}
 
-        let repo = repo_and_query
-            .first()
-            .expect(&format!("Could not parse repo for package {:?}", url))
-            .to_string()
-            .replace(".git", "");
+/// the targets of a package searched besides it's library, from
+/// `#[redefined_attr(search(examples, tests, benches))]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchTarget {
+    Examples,
+    Tests,
+    Benches,
+}
 
-        Self::Github(repo, commit)
+impl SearchTarget {
+    /// the targets of the `#[redefined_attr(..)]` attributes only used by
+    /// `redefined_remote!`
+    pub fn from_attrs(remote_attrs: &[(TokenStream, Vec<TypeAttribute>)]) -> syn::Result<Vec<Self>> {
+        remote_attrs
+            .iter()
+            .flat_map(|(_, symbols)| symbols.iter())
+            .filter(|attr| attr.symbol == SEARCH)
+            .filter_map(|attr| attr.list_idents.clone())
+            .flatten()
+            .map(|ident| match ident.to_string().as_str() {
+                "examples" => Ok(Self::Examples),
+                "tests" => Ok(Self::Tests),
+                "benches" => Ok(Self::Benches),
+                _ => Err(syn::Error::new(ident.span(), "Expected `examples`, `tests` or `benches` in `search(..)`")),
+            })
+            .collect()
     }
 
-    fn fetch_from_cargo(&self, version: &str) -> DirEntry {
+    /// the directory of the target's files, from the package's directory
+    fn dir(&self) -> &'static str {
         match self {
-            PackageKind::CratesIo(_) => self.fetch_from_cargo_crates_io(version),
-            PackageKind::Github(..) => self.fetch_from_cargo_git(),
+            SearchTarget::Examples => "examples",
+            SearchTarget::Tests => "tests",
+            SearchTarget::Benches => "benches",
         }
     }
+}
 
-    fn fetch_from_cargo_crates_io(&self, version: &str) -> DirEntry {
-        let package_name = match self {
-            PackageKind::CratesIo(p) => p,
-            _ => unreachable!("cannot fetch from github for crates-io"),
-        };
+/// the files of a package searched for a type without a library target, all of
+/// it's files except it's build script and the targets that aren't searched
+fn package_files(package_dir: &Path, search: &[SearchTarget]) -> syn::Result<Vec<PathBuf>> {
+    let skipped = [SearchTarget::Examples, SearchTarget::Tests, SearchTarget::Benches]
+        .into_iter()
+        .filter(|target| !search.contains(target))
+        .map(|target| package_dir.join(target.dir()))
+        .collect::<Vec<_>>();
+    let build_script = manifest(package_dir)
+        .get("package")
+        .and_then(|package| package.get("build"))
+        .and_then(|build| build.as_str())
+        .map_or(package_dir.join("build.rs"), |build| package_dir.join(build));
+
+    let mut paths = Vec::new();
+    get_all_files(package_dir, &mut paths)?;
+    paths.retain(|path| *path != build_script && !skipped.iter().any(|dir| path.starts_with(dir)));
+
+    Ok(paths)
+}
 
-        let mut cargo_dir_path = cargo_home_dir();
-        cargo_dir_path.push("registry/src");
+/// the files of the searched targets
+fn target_files(package_dir: &Path, search: &[SearchTarget]) -> syn::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for target in search {
+        let dir = package_dir.join(target.dir());
+        if dir.is_dir() {
+            get_all_files(&dir, &mut paths)?;
+        }
+    }
+
+    Ok(paths)
+}
+
+/// the exact packages of the dependencies of the crate being compiled by the
+/// name they are used with, `None` if `cargo metadata` can't be loaded
+pub fn current_dependencies() -> Option<HashMap<String, String>> {
+    let metadata = CargoMetadata::load()?;
+    let current = metadata.package_in(&current_manifest_dir()?)?;
+
+    let dependencies = metadata
+        .dependencies(&current.id)
+        .into_iter()
+        .map(|(name, dependency)| (name, format!("{}@={}", dependency.name, dependency.version)))
+        .collect();
 
-        let all_dirs = std::fs::read_dir(&cargo_dir_path)
-            .expect(&format!("Could not read cargo crates-io dir for {:?}", cargo_dir_path))
-            .collect::<Result<Vec<_>, _>>()
-            .expect(&format!("Coult not read subdirectories for: {:?}", cargo_dir_path));
+    Some(dependencies)
+}
+
+/// the manifest dir of the crate being compiled
+fn current_manifest_dir() -> Option<PathBuf> {
+    std::env::var("CARGO_MANIFEST_DIR").ok().map(PathBuf::from)
+}
+
+/// whether a version satisfies the requirement, if there is one
+fn matches(version_req: Option<&VersionReq>, version: &str) -> bool {
+    version_req.is_none_or(|version_req| Version::parse(version).is_ok_and(|version| version_req.matches(&version)))
+}
 
-        if all_dirs.is_empty() {
-            panic!("{}", format!("No subdirectories for: {:?}", cargo_dir_path));
+/// the only locked version of a package, erroring with the versions if there
+/// are several
+fn single_version<T>(name: &str, version_req: Option<&VersionReq>, candidates: Vec<T>, version: impl Fn(&T) -> String) -> std::io::Result<T> {
+    match <[_; 1]>::try_from(candidates) {
+        Ok([candidate]) => Ok(candidate),
+        Err(candidates) if candidates.is_empty() => {
+            let spec = version_req.map_or(name.to_string(), |version_req| format!("{name}@{version_req}"));
+            Err(std::io::Error::new(std::io::ErrorKind::NotFound, format!("Could not find package '{spec}' in the cargo lock")))
         }
+        Err(candidates) => {
+            let versions = candidates.iter().map(version).collect::<Vec<_>>();
+            Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Several versions of package '{name}' are locked ({}), use '{name}@<VERSION>'", versions.join(", ")),
+            ))
+        }
+    }
+}
 
-        all_dirs
-            .into_iter()
-            .map(|dir| dir.path())
-            .flat_map(|crates_io_path| {
-                std::fs::read_dir(&crates_io_path)
-                    .expect(&format!("Could not read cargo crates-io dir for {:?}", crates_io_path))
-                    .collect::<Result<Vec<_>, _>>()
-                    .expect(&format!("Coult not read subdirectories for: {:?}", crates_io_path))
-                    .into_iter()
-            })
-            .find(|c| c.path().is_dir() && c.file_name().to_str().unwrap() == &format!("{package_name}-{version}"))
-            .expect(&format!("Could not find crates-io package with name: {package_name}-{version}"))
+/// the directory of the package named `name` in `dir` or it's subdirectories,
+/// skipping `target` and hidden directories
+fn find_local_package(dir: &Path, name: &str) -> Option<PathBuf> {
+    let package_name = manifest(dir)
+        .get("package")
+        .and_then(|package| package.get("name"))
+        .and_then(|package_name| package_name.as_str())
+        .map(str::to_string);
+    if package_name.as_deref() == Some(name) {
+        return Some(dir.to_path_buf());
     }
 
-    fn fetch_from_cargo_git(&self) -> DirEntry {
-        let (package_name, commit) = match self {
-            PackageKind::Github(p, c) => (p, c),
-            _ => unreachable!("cannot fetch for from github"),
-        };
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let file_name = path
+                .file_name()
+                .and_then(|file_name| file_name.to_str())
+                .unwrap_or_default();
+            path.is_dir() && file_name != "target" && !file_name.starts_with('.')
+        })
+        .find_map(|path| find_local_package(&path, name))
+}
 
-        let mut cargo_dir_path = cargo_home_dir();
-        cargo_dir_path.push("git/checkouts");
+#[cfg(test)]
+mod tests {
+    use syn::{parse::Parse, parse_quote, Attribute};
 
-        let dir_value = std::fs::read_dir(&cargo_dir_path)
-            .expect(&format!("Could not read cargo git dir for {:?}", cargo_dir_path))
-            .collect::<Result<Vec<_>, _>>()
-            .expect(&format!("Coult not read subdirectories for: {:?}", cargo_dir_path))
-            .into_iter()
-            .find(|sub_dir| {
-                let file_name = sub_dir.file_name();
-                let p = file_name.to_str().unwrap();
-                p.starts_with(&format!("{package_name}-")) && p.replace(&format!("{package_name}-"), "").len() == 16
+    use super::*;
+    use crate::{attributes::ContainerAttributes, remote::types::test_dir};
+
+    fn search(attrs: &[Attribute]) -> syn::Result<Vec<SearchTarget>> {
+        let remote_attrs = attrs
+            .iter()
+            .map(|attr| (quote::quote!(#attr), attr.parse_args_with(ContainerAttributes::parse).unwrap().0))
+            .collect::<Vec<_>>();
+
+        SearchTarget::from_attrs(&remote_attrs)
+    }
+
+    /// a package with every kind of target, a nested package and the
+    /// directories that are never searched
+    fn package_dir() -> PathBuf {
+        let package_dir = test_dir("package-files");
+        for path in [
+            "Cargo.toml",
+            "build.rs",
+            "src/lib.rs",
+            "src/a/mod.rs",
+            "examples/example.rs",
+            "tests/test.rs",
+            "benches/bench.rs",
+            "nested/Cargo.toml",
+            "nested/src/lib.rs",
+            "target/debug/build/out.rs",
+            ".git/hooks/hook.rs",
+        ] {
+            let path = package_dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+
+        package_dir
+    }
+
+    fn relative(package_dir: &Path, mut paths: Vec<PathBuf>) -> Vec<String> {
+        paths.sort();
+        paths
+            .iter()
+            .map(|path| {
+                path.strip_prefix(package_dir)
+                    .unwrap()
+                    .display()
+                    .to_string()
             })
-            .expect(&format!("Could not find git package directory with name: {package_name}"));
+            .collect()
+    }
 
-        let git_path = dir_value.path();
+    #[test]
+    fn test_search_targets() {
+        assert_eq!(search(&[parse_quote!(#[redefined_attr(search(examples, benches))])]).unwrap(), [SearchTarget::Examples, SearchTarget::Benches]);
+        assert_eq!(
+            search(&[parse_quote!(#[redefined_attr(search(tests))]), parse_quote!(#[redefined_attr(recursive)])]).unwrap(),
+            [SearchTarget::Tests]
+        );
+        assert!(search(&[parse_quote!(#[redefined_attr(recursive)])])
+            .unwrap()
+            .is_empty());
+        assert!(search(&[parse_quote!(#[redefined_attr(search(build))])]).is_err());
+    }
 
-        // 16
+    #[test]
+    fn test_package_files() {
+        let package_dir = package_dir();
 
-        let project_crate = std::fs::read_dir(&git_path)
-            .expect(&format!("Could not read cargo git dir for {:?}", git_path))
-            .collect::<Result<Vec<_>, _>>()
-            .expect(&format!("Coult not read subdirectories for: {:?}", git_path))
-            .into_iter()
-            .find(|c| c.path().is_dir() && c.file_name().to_str().unwrap() == &format!("{}", commit[0..7].to_string()))
-            .expect(&format!("Could not find git package with name: {}", commit[0..7].to_string()));
+        assert_eq!(relative(&package_dir, package_files(&package_dir, &[]).unwrap()), ["src/a/mod.rs", "src/lib.rs"]);
+        assert_eq!(
+            relative(&package_dir, package_files(&package_dir, &[SearchTarget::Examples, SearchTarget::Benches]).unwrap()),
+            ["benches/bench.rs", "examples/example.rs", "src/a/mod.rs", "src/lib.rs"]
+        );
+        assert_eq!(relative(&package_dir, target_files(&package_dir, &[SearchTarget::Tests]).unwrap()), ["tests/test.rs"]);
+
+        std::fs::remove_dir_all(&package_dir).unwrap();
+    }
+
+    #[test]
+    fn test_package_kind() {
+        let kind = |source: Option<&str>| PackageKind::new("foo", source.map(str::to_string), || Some(PathBuf::from("/foo")));
 
-        project_crate
+        assert!(matches!(kind(Some("registry+https://github.com/rust-lang/crates.io-index")), Ok(PackageKind::Registry(_))));
+        assert!(matches!(kind(Some("sparse+https://index.crates.io/")), Ok(PackageKind::Registry(_))));
+        assert!(matches!(kind(Some("git+https://github.com/SorellaLabs/redefined?branch=remote#0123456789abcdef")), Ok(PackageKind::Git(_))));
+        assert!(matches!(kind(None), Ok(PackageKind::Local(dir)) if dir == Path::new("/foo")));
+        assert!(kind(Some("path+file:///foo")).is_err());
+        assert!(PackageKind::new("foo", None, || None).is_err());
     }
 }
@@ -0,0 +1,424 @@
+use std::collections::HashMap;
+
+use proc_macro2::{Span, TokenStream};
+use syn::{
+    visit_mut::{self, VisitMut},
+    Expr, ExprLit, GenericParam, Ident, Lit, Path, TypePath,
+};
+
+use super::{
+    alias::{expand_alias, RemoteDefinition},
+    file_parser::RemoteItem,
+    package::current_dependencies,
+    resolver::Resolver,
+    ModulePath, RemoteName,
+};
+use crate::attributes::{
+    primitives::{is_simple_primitive, is_std_type},
+    symbol::{EXCLUDE, MAX_DEPTH, RECURSIVE},
+    type_attr::TypeAttribute,
+    type_map::TypeMap,
+};
+
+/// how many levels of nested types are mirrored without `max_depth = ..`
+const DEFAULT_MAX_DEPTH: usize = 8;
+
+/// `#[redefined_attr(recursive, max_depth = .., exclude(..))]`, also mirrors
+/// the remote types used in the fields of the remote types
+#[derive(Debug, Clone)]
+pub struct Recursion {
+    /// how many levels of nested types are mirrored (`1` only mirrors the types
+    /// of the fields of the listed types)
+    pub max_depth: usize,
+    /// the types that aren't mirrored, i.e. because they are mirrored by
+    /// another call of the macro. Matched by their identifier as written in the
+    /// field or by the end of their full path (i.e. `a::Error` for
+    /// `foo::a::Error`)
+    pub exclude:   Vec<Path>,
+}
+
+impl Recursion {
+    /// the recursion of the `#[redefined_attr(..)]` attributes only used by
+    /// `redefined_remote!`, `None` without `recursive`
+    pub fn from_attrs(remote_attrs: &[(TokenStream, Vec<TypeAttribute>)]) -> syn::Result<Option<Self>> {
+        let symbols = remote_attrs
+            .iter()
+            .flat_map(|(_, symbols)| symbols.iter().cloned())
+            .collect::<Vec<_>>();
+
+        if !RECURSIVE.contained_in(&symbols) {
+            let options = remote_attrs
+                .iter()
+                .find(|(_, symbols)| MAX_DEPTH.contained_in(symbols) || EXCLUDE.contained_in(symbols));
+            return match options {
+                Some((attr, _)) => Err(syn::Error::new_spanned(attr, "The `max_depth` and `exclude` attributes require `recursive`")),
+                None => Ok(None),
+            };
+        }
+
+        let max_depth = match MAX_DEPTH.find_type_attr(&symbols) {
+            Some(attr) => match &attr.nv_tokens {
+                Some(Expr::Lit(ExprLit { lit: Lit::Int(depth), .. })) => depth.base10_parse()?,
+                other => return Err(syn::Error::new_spanned(other, "Expected an integer depth for `max_depth`")),
+            },
+            None => DEFAULT_MAX_DEPTH,
+        };
+        let exclude = symbols
+            .iter()
+            .filter(|attr| attr.symbol == EXCLUDE)
+            .filter_map(|attr| attr.list_paths.clone())
+            .flatten()
+            .collect();
+
+        Ok(Some(Self { max_depth, exclude }))
+    }
+
+    /// whether an item is excluded by the end of it's full path (i.e.
+    /// `ruint::bits::Bits`)
+    fn excludes(&self, item_path: &str) -> bool {
+        let item_segments = item_path.split("::").collect::<Vec<_>>();
+
+        self.exclude.iter().any(|path| {
+            path.segments.len() <= item_segments.len()
+                && path
+                    .segments
+                    .iter()
+                    .rev()
+                    .zip(item_segments.iter().rev())
+                    .all(|(segment, item_segment)| segment.ident == item_segment)
+        })
+    }
+
+    /// adds the remote types used in the fields of the definitions, and the
+    /// ones used in their fields, up to `max_depth`
+    ///
+    /// errors if two of the types would have new types with the same name
+    /// (i.e. `a::Error` and `b::Error`), since the fields are named by the
+    /// type's identifier
+    pub fn add_nested_types(
+        &self,
+        resolver: &mut Resolver,
+        definitions: &mut Vec<(RemoteName, RemoteDefinition)>,
+        type_map: &TypeMap,
+    ) -> syn::Result<()> {
+        // the definition each definition is nested in, `None` for the listed ones
+        let mut parents: Vec<Option<usize>> = vec![None; definitions.len()];
+        let mut depths = vec![0; definitions.len()];
+        let dependencies = current_dependencies();
+
+        let mut idx = 0;
+        while idx < definitions.len() {
+            let span = definitions[idx].0.ident.span();
+            let nested = self.nested_types(resolver, &mut definitions[idx].1, type_map, dependencies.as_ref(), span)?;
+
+            for (name, definition) in nested {
+                if definitions
+                    .iter()
+                    .any(|(_, other)| other.item_path() == definition.item_path())
+                {
+                    continue;
+                }
+
+                if depths[idx] >= self.max_depth {
+                    let mut chain = vec![definition.item_path()];
+                    let mut parent = Some(idx);
+                    while let Some(parent_idx) = parent {
+                        chain.push(definitions[parent_idx].1.item_path());
+                        parent = parents[parent_idx];
+                    }
+                    let chain = chain
+                        .iter()
+                        .rev()
+                        .map(|item_path| format!("`{item_path}`"))
+                        .collect::<Vec<_>>()
+                        .join(" -> ");
+
+                    return Err(syn::Error::new(
+                        span,
+                        format!(
+                            "{chain} is nested deeper than the `max_depth` of {}, raise it or use `exclude({})`",
+                            self.max_depth,
+                            definition.item_path()
+                        ),
+                    ));
+                }
+
+                let new_type = new_type_name(&name);
+                if let Some((_, other)) = definitions
+                    .iter()
+                    .find(|(other_name, _)| new_type_name(other_name) == new_type)
+                {
+                    return Err(syn::Error::new(
+                        span,
+                        format!(
+                            "`{}` and `{}` would both be mirrored as `{new_type}`, map one of them with `map_types(..)` or use `exclude({})`",
+                            other.item_path(),
+                            definition.item_path(),
+                            definition.item_path()
+                        ),
+                    ));
+                }
+
+                definitions.push((name, definition));
+                parents.push(Some(idx));
+                depths.push(depths[idx] + 1);
+            }
+
+            idx += 1;
+        }
+
+        Ok(())
+    }
+
+    /// the remote types used in the fields of a definition, rewriting the
+    /// paths of the fields to them to their names (i.e. `bits::Foo` -> `Foo`),
+    /// so they use the new types generated next to the definition's
+    fn nested_types(
+        &self,
+        resolver: &mut Resolver,
+        remote_definition: &mut RemoteDefinition,
+        type_map: &TypeMap,
+        dependencies: Option<&HashMap<String, String>>,
+        span: Span,
+    ) -> syn::Result<Vec<(RemoteName, RemoteDefinition)>> {
+        let definition = &mut remote_definition.definition;
+        let generics = definition
+            .item
+            .generics
+            .params
+            .iter()
+            .filter_map(|param| match param {
+                GenericParam::Type(param) => Some(param.ident.clone()),
+                GenericParam::Const(param) => Some(param.ident.clone()),
+                GenericParam::Lifetime(_) => None,
+            })
+            .collect();
+
+        let mut visitor = NestedTypes {
+            resolver,
+            recursion: self,
+            type_map,
+            dependencies,
+            package_name: definition.package_name.clone(),
+            module_path: definition.module_path.clone(),
+            generics,
+            span,
+            found: Vec::new(),
+            error: None,
+        };
+        visitor.visit_data_mut(&mut definition.item.data);
+
+        match visitor.error {
+            Some(error) => Err(error),
+            None => Ok(visitor.found),
+        }
+    }
+}
+
+/// finds the remote types in the field types of a definition
+struct NestedTypes<'a> {
+    resolver:     &'a mut Resolver,
+    recursion:    &'a Recursion,
+    type_map:     &'a TypeMap,
+    /// the packages of the dependencies of the crate being compiled by the
+    /// name they are used with, `None` if `cargo metadata` can't be loaded
+    dependencies: Option<&'a HashMap<String, String>>,
+    /// the package and module of the definition, the field types are resolved
+    /// from
+    package_name: String,
+    module_path:  Vec<String>,
+    /// the generic parameters of the definition
+    generics:     Vec<Ident>,
+    span:         Span,
+    found:        Vec<(RemoteName, RemoteDefinition)>,
+    error:        Option<syn::Error>,
+}
+
+impl NestedTypes<'_> {
+    /// the new type of a field's type that's mirrored, the same types as
+    /// `parse_type_to_redefined` redefines are mirrored
+    fn nested_type(&mut self, type_path: &TypePath) -> syn::Result<Option<(RemoteName, RemoteDefinition)>> {
+        let path = &type_path.path;
+        let Some(last) = path.segments.last() else { return Ok(None) };
+        let ident = last.ident.to_string();
+
+        if is_simple_primitive(&ident)
+            || is_std_type(path)
+            || ident == "Self"
+            || self.generics.contains(&last.ident)
+            || self
+                .recursion
+                .exclude
+                .iter()
+                .any(|exclude| exclude.is_ident(&last.ident))
+        {
+            return Ok(None);
+        }
+
+        let segments = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>();
+        // types that aren't in a locked package (i.e. from `std`) are left alone
+        let item = match self
+            .resolver
+            .resolve(&self.package_name, &self.module_path, &segments)
+            .as_slice()
+        {
+            [] => return Ok(None),
+            [item] => item.clone(),
+            results => {
+                let candidates = results
+                    .iter()
+                    .map(|result| format!("`{}`", result.item_path()))
+                    .collect::<Vec<_>>();
+                return Err(syn::Error::new(
+                    self.span,
+                    format!("Too Many Definitions Of The Field Type `{}`: {}", segments.join("::"), candidates.join(", ")),
+                ));
+            }
+        };
+
+        if self.recursion.excludes(&item.item_path()) {
+            return Ok(None);
+        }
+
+        let name = RemoteName {
+            path:   syn::parse_str(&public_path(self.resolver, &item, self.dependencies, self.span)?)?,
+            ident:  Ident::new(&item.ident().to_string(), self.span),
+            rename: None,
+        };
+        let definition = match item {
+            RemoteItem::Type(definition) => RemoteDefinition { definition, alias: None },
+            RemoteItem::Alias(alias) => expand_alias(self.resolver, alias, self.span)?,
+        };
+
+        Ok(Some((name, definition)))
+    }
+}
+
+impl VisitMut for NestedTypes<'_> {
+    fn visit_type_path_mut(&mut self, type_path: &mut TypePath) {
+        if self.error.is_some() || type_path.qself.is_some() || self.type_map.get(&type_path.path).is_some() {
+            return;
+        }
+
+        // associated types (i.e. `T::Assoc`) are kept unchanged
+        let path = &type_path.path;
+        if path.segments.len() > 1 {
+            let first = &path.segments[0].ident;
+            if first == "Self" || self.generics.contains(first) {
+                return;
+            }
+        }
+
+        match self.nested_type(type_path) {
+            // named by the item's identifier, in case it's imported with another name
+            Ok(Some(nested)) => {
+                let path = &mut type_path.path;
+                if let Some(mut last) = path.segments.pop().map(|pair| pair.into_value()) {
+                    last.ident = nested.0.ident.clone();
+                    path.segments.clear();
+                    path.leading_colon = None;
+                    path.segments.push(last);
+                }
+                self.found.push(nested);
+            }
+            Ok(None) => (),
+            Err(error) => {
+                self.error = Some(error);
+                return;
+            }
+        }
+
+        visit_mut::visit_type_path_mut(self, type_path);
+    }
+}
+
+/// the name of the new type of a remote type
+fn new_type_name(name: &RemoteName) -> String {
+    match &name.rename {
+        Some(rename) => rename.to_string(),
+        None => format!("{}Redefined", name.ident),
+    }
+}
+
+/// the path of an item from outside of it's crate, it's re-export from the
+/// crate's root if it has one (i.e. `ruint::Bits` for `ruint::bit_arr::Bits`),
+/// starting with the name the crate being compiled uses for the item's package
+///
+/// errors if the item can't be named from outside of it's crate, or if it's
+/// package isn't a dependency of the crate being compiled (i.e. it's a
+/// dependency of the remote type's package), since it's path is the source
+/// type of it's new type. Without `cargo metadata` the crate's own name is
+/// used
+fn public_path(resolver: &mut Resolver, item: &RemoteItem, dependencies: Option<&HashMap<String, String>>, span: Span) -> syn::Result<String> {
+    let crate_name = match dependencies {
+        Some(dependencies) => dependencies
+            .iter()
+            .filter(|(_, package)| *package == item.package_name())
+            .map(|(name, _)| name.as_str())
+            .min()
+            .ok_or_else(|| {
+                syn::Error::new(
+                    span,
+                    format!(
+                        "`{}` can't be named from this crate, `{}` isn't one of it's dependencies. Add it as a dependency, map it with \
+                         `map_types(..)` or use `exclude({})`",
+                        item.item_path(),
+                        item.package_name(),
+                        item.item_path()
+                    ),
+                )
+            })?,
+        None => item.crate_name(),
+    };
+
+    let root = resolver.find(item.package_name(), &ModulePath::Root(Vec::new()), &item.ident().to_string());
+    if let [found] = root.as_slice() {
+        if found.file_path() == item.file_path() && found.ident() == item.ident() {
+            return Ok(format!("{crate_name}::{}", item.ident()));
+        }
+    }
+
+    let is_public = item.is_pub()
+        && resolver
+            .tree(item.package_name())
+            .is_some_and(|tree| tree.is_public_module(item.module_path()));
+    if !is_public {
+        return Err(syn::Error::new(
+            span,
+            format!(
+                "`{}` can't be named outside of `{}`, it isn't public or re-exported from the crate's root. Map it with `map_types(..)` or use \
+                 `exclude({})`",
+                item.item_path(),
+                item.crate_name(),
+                item.item_path()
+            ),
+        ));
+    }
+
+    Ok(std::iter::once(crate_name.to_string())
+        .chain(item.module_path().iter().cloned())
+        .chain(std::iter::once(item.ident().to_string()))
+        .collect::<Vec<_>>()
+        .join("::"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_excludes() {
+        let recursion = Recursion { max_depth: DEFAULT_MAX_DEPTH, exclude: vec![syn::parse_quote!(a::Error), syn::parse_quote!(Bits)] };
+
+        assert!(recursion.excludes("foo::a::Error"));
+        assert!(recursion.excludes("ruint::bits::Bits"));
+        assert!(!recursion.excludes("foo::b::Error"));
+        // the whole segment is matched
+        assert!(!recursion.excludes("foo::ba::Error"));
+        assert!(!recursion.excludes("Error"));
+    }
+}
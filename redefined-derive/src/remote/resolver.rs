@@ -0,0 +1,191 @@
+use std::{collections::HashMap, rc::Rc};
+
+use super::{file_parser::RemoteItem, module_tree::ModuleTree, package::Package, ModulePath};
+
+/// how many `use` items are followed before giving up, so cyclic globs (i.e.
+/// `pub use self::*;`) end
+const MAX_REEXPORT_DEPTH: usize = 16;
+
+/// a module of a package (i.e. `("ruint", ["algorithms"])`)
+type Location = (String, Vec<String>);
+
+/// follows `pub use` re-exports to the definitions of remote types, across the
+/// packages in `Cargo.lock` (i.e. `alloy::primitives::Address` ->
+/// `alloy_primitives::bits::Address`)
+///
+/// paths written in a crate (i.e. the target of a type alias) also see the
+/// crate's private imports
+#[derive(Default)]
+pub struct Resolver {
+    /// the module trees of the packages by name, `None` if the package isn't
+    /// locked or it's sources aren't in the cargo home
+    trees: HashMap<String, Option<Rc<ModuleTree>>>,
+}
+
+impl Resolver {
+    pub fn tree(&mut self, package_name: &str) -> Option<Rc<ModuleTree>> {
+        self.trees
+            .entry(package_name.to_string())
+            .or_insert_with(|| {
+                Package::new(package_name.to_string())
+                    .ok()
+                    .and_then(|package| package.module_tree())
+                    .map(Rc::new)
+            })
+            .clone()
+    }
+
+    /// the types and aliases named `name` in the given modules of the package,
+    /// following it's re-exports if none are defined there
+    pub fn find(&mut self, package_name: &str, modules: &ModulePath, name: &str) -> Vec<RemoteItem> {
+        let Some(tree) = self.tree(package_name) else { return Vec::new() };
+        let defined = tree.find(modules, name);
+        if !defined.is_empty() {
+            return defined;
+        }
+
+        let locations = self.walk_modules(package_name, Vec::new(), modules.segments(), 0, false);
+        let results = locations
+            .into_iter()
+            .flat_map(|(package_name, module_path)| self.resolve_in_module(&package_name, &module_path, name, 0, false))
+            .collect();
+
+        dedup(results)
+    }
+
+    /// the types and aliases at `path`, as written in the module `module_path`
+    /// of the package (i.e. `Uint` in `pub type U256 = Uint<256, 4>;`)
+    pub fn resolve(&mut self, package_name: &str, module_path: &[String], path: &[String]) -> Vec<RemoteItem> {
+        let results = self.resolve_item(package_name, module_path, path, 0);
+
+        dedup(results)
+    }
+
+    /// the types and aliases at `path`, as written in the module `module_path`
+    fn resolve_item(&mut self, package_name: &str, module_path: &[String], path: &[String], depth: usize) -> Vec<RemoteItem> {
+        let Some((name, modules)) = path.split_last() else { return Vec::new() };
+        if modules.is_empty() {
+            return self.resolve_in_module(package_name, module_path, name, depth, true);
+        }
+
+        self.resolve_module(package_name, module_path, modules, depth)
+            .into_iter()
+            .flat_map(|(other_package, other_module)| {
+                let in_scope = other_package == package_name;
+                self.resolve_in_module(&other_package, &other_module, name, depth, in_scope)
+            })
+            .collect()
+    }
+
+    /// the types and aliases named `name` in a module, defined or imported
+    /// there (only re-exported unless `in_scope`)
+    fn resolve_in_module(&mut self, package_name: &str, module_path: &[String], name: &str, depth: usize, in_scope: bool) -> Vec<RemoteItem> {
+        let Some(tree) = self.tree(package_name) else { return Vec::new() };
+        if depth > MAX_REEXPORT_DEPTH {
+            return Vec::new();
+        }
+
+        let defined = tree.find(&ModulePath::Root(module_path.to_vec()), name);
+        if !defined.is_empty() {
+            return defined;
+        }
+
+        let found = tree
+            .uses_in(module_path, in_scope)
+            .filter(|use_item| use_item.name.as_deref() == Some(name))
+            .flat_map(|use_item| self.resolve_item(package_name, module_path, &use_item.target, depth + 1))
+            .collect::<Vec<_>>();
+        if !found.is_empty() {
+            return found;
+        }
+
+        tree.uses_in(module_path, in_scope)
+            .filter(|use_item| use_item.name.is_none())
+            .flat_map(|glob| self.resolve_item(package_name, module_path, &[glob.target.as_slice(), &[name.to_string()]].concat(), depth + 1))
+            .collect()
+    }
+
+    /// the modules at `path`, as written in the module `module_path`
+    fn resolve_module(&mut self, package_name: &str, module_path: &[String], path: &[String], depth: usize) -> Vec<Location> {
+        let Some(tree) = self.tree(package_name) else { return Vec::new() };
+        let Some((first, rest)) = path.split_first() else { return vec![(package_name.to_string(), module_path.to_vec())] };
+
+        match first.as_str() {
+            "crate" => self.walk_modules(package_name, Vec::new(), rest, depth, true),
+            "self" => self.walk_modules(package_name, module_path.to_vec(), rest, depth, true),
+            "super" => {
+                let parent = &module_path[..module_path.len().saturating_sub(1)];
+                self.resolve_module(package_name, parent, rest, depth)
+            }
+            _ if tree.is_local(module_path, first) => self.walk_modules(package_name, module_path.to_vec(), path, depth, true),
+            // an external crate, by the name it's used with in the package
+            _ => {
+                let dependency = tree
+                    .dependencies
+                    .get(first)
+                    .cloned()
+                    .unwrap_or_else(|| first.replace('_', "-"));
+                self.walk_modules(&dependency, Vec::new(), rest, depth, false)
+            }
+        }
+    }
+
+    /// the modules at `path` from the module `module_path`, following imported
+    /// modules (only re-exported unless `in_scope`)
+    fn walk_modules(&mut self, package_name: &str, module_path: Vec<String>, path: &[String], depth: usize, in_scope: bool) -> Vec<Location> {
+        let Some(tree) = self.tree(package_name) else { return Vec::new() };
+        let Some((first, rest)) = path.split_first() else { return vec![(package_name.to_string(), module_path)] };
+        if depth > MAX_REEXPORT_DEPTH {
+            return Vec::new();
+        }
+
+        let child = [module_path.as_slice(), std::slice::from_ref(first)].concat();
+        if tree.has_module(&child) {
+            return self.walk_modules(package_name, child, rest, depth, in_scope);
+        }
+
+        // `pub use a as b;`
+        let imported = tree
+            .uses_in(&module_path, in_scope)
+            .filter(|use_item| use_item.name.as_deref() == Some(first.as_str()))
+            .flat_map(|use_item| self.resolve_module(package_name, &module_path, &use_item.target, depth + 1))
+            .collect::<Vec<_>>();
+        let found = imported
+            .into_iter()
+            .flat_map(|(other_package, other_module)| {
+                let in_scope = in_scope && other_package == package_name;
+                self.walk_modules(&other_package, other_module, rest, depth + 1, in_scope)
+            })
+            .collect::<Vec<_>>();
+        if !found.is_empty() {
+            return found;
+        }
+
+        // `pub use a::*;`
+        let globs = tree
+            .uses_in(&module_path, in_scope)
+            .filter(|use_item| use_item.name.is_none())
+            .flat_map(|glob| self.resolve_module(package_name, &module_path, &glob.target, depth + 1))
+            .collect::<Vec<_>>();
+        globs
+            .into_iter()
+            .flat_map(|(other_package, other_module)| {
+                let in_scope = in_scope && other_package == package_name;
+                self.walk_modules(&other_package, other_module, path, depth + 1, in_scope)
+            })
+            .collect()
+    }
+}
+
+/// removes items found through several paths
+fn dedup(mut results: Vec<RemoteItem>) -> Vec<RemoteItem> {
+    let mut seen = Vec::new();
+    results.retain(|result| {
+        let key = (result.file_path().clone(), result.ident().clone());
+        let is_new = !seen.contains(&key);
+        seen.push(key);
+        is_new
+    });
+
+    results
+}
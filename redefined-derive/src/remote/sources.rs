@@ -0,0 +1,285 @@
+use std::path::{Path, PathBuf};
+
+use super::{
+    metadata::CargoMetadata,
+    module_tree::manifest,
+    types::{cargo_home_dir, read_dirs, workspace_dir},
+};
+
+/// the sources of crates-io in `Cargo.lock`
+const CRATES_IO: [&str; 2] = ["registry+https://github.com/rust-lang/crates.io-index", "sparse+https://index.crates.io/"];
+
+/// how many `replace-with = ".."` are followed before giving up, so cycles end
+const MAX_REPLACEMENTS: usize = 16;
+
+/// the source a registry is replaced with in `.cargo/config.toml`, which is
+/// read instead of the registry
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplacedSource {
+    /// `directory = ".."`, the unpacked packages (i.e. from `cargo vendor`)
+    Directory(PathBuf),
+    /// `local-registry = ".."`, the `.crate` archives of the packages
+    LocalRegistry(PathBuf),
+}
+
+/// the source replacing a registry of `Cargo.lock` (i.e.
+/// `registry+https://github.com/rust-lang/crates.io-index`), following
+/// `replace-with = ".."`, `None` if it isn't replaced by a local source
+pub fn replaced_source(registry: &str) -> Option<ReplacedSource> {
+    replaced_source_in(&cargo_configs(), registry)
+}
+
+/// the source replacing a registry in the cargo configs, closest first
+fn replaced_source_in(configs: &[(toml::Table, PathBuf)], registry: &str) -> Option<ReplacedSource> {
+    let url = registry.strip_prefix("registry+").unwrap_or(registry);
+
+    // crates-io, or the name of the registry (`[registries.<NAME>]`) or source
+    // (`[source.<NAME>]`) with the url
+    let mut name = if CRATES_IO.contains(&registry) {
+        "crates-io".to_string()
+    } else {
+        configs.iter().find_map(|(config, _)| {
+            ["registries", "source"].into_iter().find_map(|table| {
+                config
+                    .get(table)?
+                    .as_table()?
+                    .iter()
+                    .find(|(_, source)| {
+                        [source.get("index"), source.get("registry")]
+                            .into_iter()
+                            .flatten()
+                            .any(|index| index.as_str() == Some(url))
+                    })
+                    .map(|(name, _)| name.clone())
+            })
+        })?
+    };
+
+    for _ in 0..MAX_REPLACEMENTS {
+        if let Some((directory, base_dir)) = source_value(configs, &name, "directory") {
+            return Some(ReplacedSource::Directory(base_dir.join(directory)));
+        }
+        if let Some((local_registry, base_dir)) = source_value(configs, &name, "local-registry") {
+            return Some(ReplacedSource::LocalRegistry(base_dir.join(local_registry)));
+        }
+
+        name = source_value(configs, &name, "replace-with")?.0;
+    }
+
+    None
+}
+
+/// the directory of a package in a directory source, which `cargo vendor`
+/// names `<NAME>` or `<NAME>-<VERSION>`
+pub fn vendored_package(vendor_dir: &Path, package_name: &str, version: &str) -> Option<PathBuf> {
+    [vendor_dir.join(format!("{package_name}-{version}")), vendor_dir.join(package_name)]
+        .into_iter()
+        .chain(read_dirs(vendor_dir))
+        .find(|package_dir| {
+            let manifest = manifest(package_dir);
+            let package = manifest.get("package");
+            let field = |key: &str| {
+                package
+                    .and_then(|package| package.get(key))
+                    .and_then(|value| value.as_str())
+            };
+
+            field("name") == Some(package_name) && field("version") == Some(version)
+        })
+}
+
+/// unpacks a `.crate` archive (i.e.
+/// `registry/cache/<INDEX>/ruint-1.12.0.crate`) into the target directory,
+/// once, returning the package's directory
+pub fn unpack_crate(archive: &Path) -> Option<PathBuf> {
+    unpack_crate_in(archive, &target_dir()?.join("redefined").join("crates"))
+}
+
+/// unpacks a `.crate` archive into `unpack_dir`, once
+fn unpack_crate_in(archive: &Path, unpack_dir: &Path) -> Option<PathBuf> {
+    let file_stem = archive.file_stem()?.to_str()?;
+    let package_dir = unpack_dir.join(file_stem);
+    if package_dir.is_dir() {
+        return Some(package_dir);
+    }
+
+    // unpacked next to it's directory and then moved, so other expansions never
+    // read a partially unpacked package
+    let tmp_dir = unpack_dir.join(format!(".{file_stem}-{}", std::process::id()));
+    let file = std::fs::File::open(archive).ok()?;
+    let unpacked = tar::Archive::new(flate2::read::GzDecoder::new(file)).unpack(&tmp_dir);
+    // the archive has a single `<NAME>-<VERSION>` directory
+    if unpacked.is_ok() && !package_dir.is_dir() {
+        let _ = std::fs::rename(tmp_dir.join(file_stem), &package_dir);
+    }
+    let _ = std::fs::remove_dir_all(&tmp_dir);
+
+    package_dir.is_dir().then_some(package_dir)
+}
+
+/// the target directory of the workspace
+fn target_dir() -> Option<PathBuf> {
+    if let Some(metadata) = CargoMetadata::load() {
+        return Some(metadata.target_directory.clone());
+    }
+
+    std::env::var("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .ok()
+        .or_else(|| Some(workspace_dir().ok()?.join("target")))
+}
+
+/// the cargo config files applying to the crate being compiled (the
+/// `.cargo/config.toml` of it's directory and it's ancestors, then the cargo
+/// home's), closest first, with the directory their paths are relative to
+fn cargo_configs() -> Vec<(toml::Table, PathBuf)> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .ok()
+        .or_else(|| workspace_dir().ok());
+    let mut config_dirs = manifest_dir
+        .iter()
+        .flat_map(|manifest_dir| manifest_dir.ancestors())
+        .map(|dir| dir.join(".cargo"))
+        .collect::<Vec<_>>();
+    if let Some(cargo_home) = cargo_home_dir()
+        .ok()
+        .filter(|cargo_home| !config_dirs.contains(cargo_home))
+    {
+        config_dirs.push(cargo_home);
+    }
+
+    config_dirs
+        .into_iter()
+        .filter_map(|config_dir| {
+            let path = ["config.toml", "config"]
+                .map(|file_name| config_dir.join(file_name))
+                .into_iter()
+                .find(|path| path.is_file())?;
+            let config = std::fs::read_to_string(&path)
+                .ok()?
+                .parse::<toml::Table>()
+                .ok()?;
+
+            // paths are relative to the directory containing `.cargo`
+            Some((config, config_dir.parent()?.to_path_buf()))
+        })
+        .collect()
+}
+
+/// the value of `[source.<NAME>] <KEY> = ".."` in the closest config setting
+/// it, with the directory it's relative to
+fn source_value(configs: &[(toml::Table, PathBuf)], name: &str, key: &str) -> Option<(String, PathBuf)> {
+    configs.iter().find_map(|(config, base_dir)| {
+        let value = config.get("source")?.get(name)?.get(key)?.as_str()?;
+        Some((value.to_string(), base_dir.clone()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+    use crate::remote::types::test_dir;
+
+    fn config(contents: &str, base_dir: &str) -> (toml::Table, PathBuf) {
+        (toml::from_str(contents).unwrap(), PathBuf::from(base_dir))
+    }
+
+    #[test]
+    fn test_replaced_source() {
+        let configs = [
+            config(
+                r#"
+                [source.crates-io]
+                replace-with = "vendored-sources"
+
+                [source.vendored-sources]
+                directory = "vendor"
+                "#,
+                "/workspace",
+            ),
+            config(
+                r#"
+                [registries.private]
+                index = "https://example.com/index"
+
+                [source.private]
+                replace-with = "private-local"
+
+                [source.private-local]
+                local-registry = "/registry"
+
+                [source.cycle]
+                registry = "https://example.com/cycle"
+                replace-with = "cycle"
+                "#,
+                "/home",
+            ),
+        ];
+
+        assert_eq!(replaced_source_in(&configs, CRATES_IO[0]), Some(ReplacedSource::Directory(PathBuf::from("/workspace/vendor"))));
+        assert_eq!(replaced_source_in(&configs, CRATES_IO[1]), Some(ReplacedSource::Directory(PathBuf::from("/workspace/vendor"))));
+        assert_eq!(
+            replaced_source_in(&configs, "registry+https://example.com/index"),
+            Some(ReplacedSource::LocalRegistry(PathBuf::from("/registry")))
+        );
+        assert_eq!(replaced_source_in(&configs, "registry+https://example.com/cycle"), None);
+        assert_eq!(replaced_source_in(&configs, "registry+https://example.com/other"), None);
+        assert_eq!(replaced_source_in(&configs[1..], CRATES_IO[0]), None);
+    }
+
+    #[test]
+    fn test_vendored_package() {
+        let vendor_dir = test_dir("sources-vendor");
+        for (dir, version) in [("foo", "0.2.0"), ("foo-0.1.0", "0.1.0")] {
+            std::fs::create_dir_all(vendor_dir.join(dir)).unwrap();
+            std::fs::write(vendor_dir.join(dir).join("Cargo.toml"), format!("[package]\nname = \"foo\"\nversion = \"{version}\"\n")).unwrap();
+        }
+
+        assert_eq!(vendored_package(&vendor_dir, "foo", "0.1.0"), Some(vendor_dir.join("foo-0.1.0")));
+        assert_eq!(vendored_package(&vendor_dir, "foo", "0.2.0"), Some(vendor_dir.join("foo")));
+        assert_eq!(vendored_package(&vendor_dir, "foo", "0.3.0"), None);
+
+        std::fs::remove_dir_all(&vendor_dir).unwrap();
+    }
+
+    #[test]
+    fn test_unpack_crate() {
+        let dir = test_dir("sources-unpack");
+        let archive = dir.join("foo-0.1.0.crate");
+
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(std::fs::File::create(&archive).unwrap(), flate2::Compression::default()));
+        for (path, contents) in [("foo-0.1.0/Cargo.toml", "[package]\nname = \"foo\"\n"), ("foo-0.1.0/src/lib.rs", "pub struct Foo;\n")] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, contents.as_bytes())
+                .unwrap();
+        }
+        builder
+            .into_inner()
+            .unwrap()
+            .finish()
+            .unwrap()
+            .flush()
+            .unwrap();
+
+        let unpack_dir = dir.join("crates");
+        let package_dir = unpack_crate_in(&archive, &unpack_dir).unwrap();
+        assert_eq!(package_dir, unpack_dir.join("foo-0.1.0"));
+        assert_eq!(std::fs::read_to_string(package_dir.join("src/lib.rs")).unwrap(), "pub struct Foo;\n");
+        // only the package's directory is left
+        assert_eq!(read_dirs(&unpack_dir), std::slice::from_ref(&package_dir));
+
+        // unpacked once
+        std::fs::remove_file(&archive).unwrap();
+        assert_eq!(unpack_crate_in(&archive, &unpack_dir), Some(package_dir));
+        assert_eq!(unpack_crate_in(&dir.join("bar-0.1.0.crate"), &unpack_dir), None);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,99 +1,125 @@
-use std::{fs::DirEntry, path::PathBuf};
+use std::{
+    ffi::OsString,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
 
-use serde::Deserialize;
+use proc_macro2::Span;
 
-pub fn workspace_dir() -> std::path::PathBuf {
-    let output = std::process::Command::new(env!("CARGO"))
+use crate::config::Config;
+
+/// the workspace directory of the last crate located, by it's manifest
+/// directory
+static CACHED_WORKSPACE_DIR: Mutex<Option<(Option<PathBuf>, PathBuf)>> = Mutex::new(None);
+
+/// the cargo running the build, or the one on the path when the macro is
+/// expanded outside of cargo (i.e. in rust-analyzer)
+pub fn cargo() -> OsString {
+    std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into())
+}
+
+/// the directory of the workspace's `Cargo.toml`, located once per crate
+pub fn workspace_dir() -> syn::Result<PathBuf> {
+    let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from);
+    let mut cached = CACHED_WORKSPACE_DIR
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    if let Some((cached_dir, workspace_dir)) = cached.as_ref() {
+        if *cached_dir == manifest_dir {
+            return Ok(workspace_dir.clone());
+        }
+    }
+
+    let workspace_err =
+        |e: &dyn std::fmt::Display| syn::Error::new(Span::call_site(), format!("Failed to locate the workspace with `cargo locate-project`: {e}"));
+    let output = std::process::Command::new(cargo())
         .arg("locate-project")
         .arg("--workspace")
         .arg("--message-format=plain")
         .output()
-        .unwrap()
-        .stdout;
-    let cargo_path = std::path::Path::new(std::str::from_utf8(&output).unwrap().trim());
-    cargo_path.parent().unwrap().to_path_buf()
-}
-
-pub fn cargo_home_dir() -> std::path::PathBuf {
-    let cargo_home_output = std::process::Command::new("sh")
-        .arg("-c")
-        .arg("echo $CARGO_HOME")
-        .output()
-        .expect("Failed to execute command")
-        .stdout;
-
-    let cargo_home = std::str::from_utf8(&cargo_home_output).unwrap().trim();
-    if cargo_home.is_empty() {
-        let home_output = std::process::Command::new("sh")
-            .arg("-c")
-            .arg("echo $HOME")
-            .output()
-            .expect("Failed to execute command")
-            .stdout;
-        let home = std::str::from_utf8(&home_output).unwrap().trim();
-
-        std::path::Path::new(&format!("{home}/.cargo")).to_path_buf()
-    } else {
-        std::path::Path::new(cargo_home).to_path_buf()
+        .map_err(|e| workspace_err(&e))?;
+    if !output.status.success() {
+        return Err(workspace_err(&String::from_utf8_lossy(&output.stderr).trim()));
     }
-}
 
-#[derive(Deserialize, Debug)]
-pub struct CratesIoCallRequest {
-    #[serde(rename = "crate")]
-    pub crate_map: CratesIoCall,
-}
+    let cargo_path = PathBuf::from(
+        String::from_utf8(output.stdout)
+            .map_err(|e| workspace_err(&e))?
+            .trim(),
+    );
+    let workspace_dir = cargo_path
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| workspace_err(&format!("{} has no parent directory", cargo_path.display())))?;
+    *cached = Some((manifest_dir, workspace_dir.clone()));
 
-#[derive(Deserialize, Debug)]
-pub struct CratesIoCall {
-    pub repository: String,
+    Ok(workspace_dir)
 }
 
-#[derive(Debug, Clone, Deserialize)]
-pub struct GithubApiFileTree {
-    pub tree: Vec<GithubApiFileTreeRow>,
-}
+/// the cargo home, from the `[remote]` config, `CARGO_HOME` or `~/.cargo`
+pub fn cargo_home_dir() -> syn::Result<PathBuf> {
+    if let Some(cargo_home) = Config::load()?.remote.cargo_home {
+        return Ok(cargo_home);
+    }
 
-#[derive(Debug, Clone, Deserialize)]
-pub struct GithubApiFileTreeRow {
-    pub path: String,
+    let env_dir = |key: &str| {
+        std::env::var_os(key)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+    };
+    env_dir("CARGO_HOME")
+        .or_else(|| env_dir("HOME").map(|home| home.join(".cargo")))
+        .ok_or_else(|| {
+            syn::Error::new(
+                Span::call_site(),
+                "Failed to locate the cargo home, neither `CARGO_HOME` nor `HOME` are set, set `cargo_home` in the `[remote]` config",
+            )
+        })
 }
 
-#[derive(Debug, Clone)]
-pub enum StructOrEnum {
-    Struct,
-    Enum,
+/// the directories in a directory
+pub fn read_dirs(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect()
 }
 
-impl StructOrEnum {
-    pub fn new_from_line(line: &str) -> Self {
-        if line.trim_start().contains("struct ") || line.trim_start().contains("pub struct ") {
-            StructOrEnum::Struct
-        } else if line.trim_start().contains("enum ") || line.trim_start().contains("pub enum ") {
-            StructOrEnum::Enum
-        } else {
-            panic!("Expected 'Struct' or 'Enum'")
-        }
-    }
+/// an empty directory for the files of a test
+#[cfg(test)]
+pub fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("redefined-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    dir
 }
 
-pub fn get_all_files(dir: &DirEntry, paths: &mut Vec<PathBuf>) {
-    let dir_values = std::fs::read_dir(dir.path())
-        .expect(&format!("Could not read dir for path {:?}", dir.path().to_str()))
-        .collect::<Result<Vec<_>, _>>()
-        .unwrap();
+/// the `.rs` files in a directory and it's subdirectories, skipping `target`,
+/// hidden directories and the directories of other packages (i.e. the sibling
+/// crates of a package in a monorepo)
+pub fn get_all_files(dir: &Path, paths: &mut Vec<PathBuf>) -> syn::Result<()> {
+    let dir_values = std::fs::read_dir(dir)
+        .and_then(|entries| entries.collect::<std::io::Result<Vec<_>>>())
+        .map_err(|e| syn::Error::new(Span::call_site(), format!("Failed to read {}: {e}", dir.display())))?;
 
     for path in dir_values {
-        if path.path().is_dir() {
-            get_all_files(&path, paths)
-        } else {
-            if let Some(ext) = path.path().extension() {
-                if let Some(e) = ext.to_str() {
-                    if e == "rs" {
-                        paths.push(path.path())
-                    }
-                }
+        let path = path.path();
+        if path.is_dir() {
+            let dir_name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or_default();
+            if dir_name != "target" && !dir_name.starts_with('.') && !path.join("Cargo.toml").is_file() {
+                get_all_files(&path, paths)?;
             }
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            paths.push(path)
         }
     }
+
+    Ok(())
 }
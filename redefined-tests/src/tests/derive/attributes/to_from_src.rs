@@ -57,7 +57,7 @@ impl ToFromSourceFieldStructB {
 #[test]
 fn test_struct_new_self_and_new_source_fn_field() {
     let struct_a = PrivateFieldStruct::default();
-    let struct_b: NonPubFieldStructB = struct_a.clone().into();
+    let struct_b: ToFromSourceFieldStructB = struct_a.clone().into();
     let struct_b_to_a: PrivateFieldStruct = struct_b.into();
     assert_eq!(struct_b_to_a, struct_a);
 }
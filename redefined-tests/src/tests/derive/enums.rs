@@ -1,11 +1,11 @@
-use redefined_derive::Redefined;
-use redefined_outside_crate_tests::{BasicStruct, ComplexOutsideEnumA};
-
-use crate::{
-    structs::{ComplexStructA, ComplexStructB, OutsideStructB},
-    RedefinedConvert,
+use redefined::Redefined;
+use redefined_test_types::{
+    enums::ComplexEnumA as ComplexOutsideEnumA,
+    structs::{BasicStruct, ComplexStructA},
 };
 
+use super::structs::derive_source::ComplexStructAA;
+
 /*
 
 
@@ -33,15 +33,15 @@ pub enum EnumB {
 fn test_enum() {
     // case 1
     let enum_a = EnumA::A;
-    let enum_b = EnumB::from_source(enum_a.clone());
+    let enum_b: EnumB = enum_a.clone().into();
     assert_eq!(EnumB::A, enum_b);
-    let enum_b_to_a: EnumA = enum_b.to_source();
+    let enum_b_to_a: EnumA = enum_b.into();
     assert_eq!(enum_b_to_a, enum_a);
 
     // case 2
     let enum_a = EnumA::C(100);
-    let enum_b = EnumB::from_source(enum_a.clone());
-    let enum_b_to_a: EnumA = enum_b.to_source();
+    let enum_b: EnumB = enum_a.clone().into();
+    let enum_b_to_a: EnumA = enum_b.into();
     assert_eq!(enum_b_to_a, enum_a);
 }
 
@@ -56,16 +56,24 @@ Complex Enum
 #[derive(Debug, PartialEq, Clone)]
 pub enum ComplexEnumA {
     A(u64),
-    B(ComplexStructA),
+    B(ComplexStructA<'static, 'static>),
     C { value: Vec<BasicStruct> },
 }
 
+#[derive(Debug, Clone, PartialEq, Default, Redefined)]
+#[redefined(BasicStruct)]
+pub struct BasicStructB {
+    pub val1: u64,
+    pub val2: f64,
+    pub val3: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Redefined)]
 #[redefined(ComplexEnumA)]
 pub enum ComplexEnumB {
     A(u64),
-    B(ComplexStructB),
-    C { value: Vec<OutsideStructB> },
+    B(ComplexStructAA<'static, 'static>),
+    C { value: Vec<BasicStructB> },
 }
 
 #[test]
@@ -89,6 +97,7 @@ fn test_complex_enum() {
     let enum_b_to_a: ComplexEnumA = enum_b.into();
     assert_eq!(enum_b_to_a, enum_a);
 }
+
 /*
 
 
@@ -101,7 +110,7 @@ Complex Outside Enum
 #[redefined(ComplexOutsideEnumA)]
 pub enum ComplexOutsideEnumB {
     A(u64),
-    C { value: Vec<OutsideStructB> },
+    C { value: Vec<BasicStructB> },
 }
 
 #[test]
@@ -119,3 +128,243 @@ fn test_complex_outside_enum() {
     let enum_b_to_a: ComplexOutsideEnumA = enum_b.into();
     assert_eq!(enum_b_to_a, enum_a);
 }
+
+/*
+
+
+
+
+
+Variant Shapes
+- unit, empty braced, empty parenthesized, named, unnamed and single field
+  variants
+*/
+#[derive(Debug, PartialEq, Clone)]
+pub enum VariantShapesEnumA {
+    Unit,
+    EmptyBraced {},
+    EmptyParen(),
+    Named { a: u64, b: String },
+    Unnamed(u64, String),
+    Single(i32),
+}
+
+#[derive(Debug, Clone, PartialEq, Redefined)]
+#[redefined(VariantShapesEnumA)]
+pub enum VariantShapesEnumB {
+    Unit,
+    EmptyBraced {},
+    EmptyParen(),
+    Named { a: u64, b: String },
+    Unnamed(u64, String),
+    Single(i32),
+}
+
+fn all_variant_shapes() -> Vec<VariantShapesEnumA> {
+    vec![
+        VariantShapesEnumA::Unit,
+        VariantShapesEnumA::EmptyBraced {},
+        VariantShapesEnumA::EmptyParen(),
+        VariantShapesEnumA::Named { a: 10, b: "named".to_string() },
+        VariantShapesEnumA::Unnamed(20, "unnamed".to_string()),
+        VariantShapesEnumA::Single(-30),
+    ]
+}
+
+#[test]
+fn test_enum_variant_shapes() {
+    for enum_a in all_variant_shapes() {
+        let enum_b: VariantShapesEnumB = enum_a.clone().into();
+        let enum_b_to_a: VariantShapesEnumA = enum_b.into();
+        assert_eq!(enum_b_to_a, enum_a);
+    }
+}
+
+/*
+
+
+
+
+
+Variant Shapes (new type)
+- same shapes as above, with variant attributes and a discriminant
+*/
+#[derive(Debug, PartialEq, Clone, Default, Redefined)]
+#[redefined_attr(derive(Debug, PartialEq, Clone, Default))]
+pub enum VariantShapesEnumC {
+    #[default]
+    Unit,
+    #[allow(dead_code)]
+    EmptyBraced {},
+    EmptyParen(),
+    Named {
+        #[allow(dead_code)]
+        a: u64,
+        b: String,
+    },
+    Unnamed(u64, #[allow(dead_code)] String),
+    Single(i32),
+}
+
+#[test]
+fn test_new_enum_variant_shapes() {
+    let all_shapes = vec![
+        VariantShapesEnumC::default(),
+        VariantShapesEnumC::EmptyBraced {},
+        VariantShapesEnumC::EmptyParen(),
+        VariantShapesEnumC::Named { a: 10, b: "named".to_string() },
+        VariantShapesEnumC::Unnamed(20, "unnamed".to_string()),
+        VariantShapesEnumC::Single(-30),
+    ];
+
+    for enum_c in all_shapes {
+        let enum_c_redefined: VariantShapesEnumCRedefined = enum_c.clone().into();
+        let enum_c_redefined_to_c: VariantShapesEnumC = enum_c_redefined.into();
+        assert_eq!(enum_c_redefined_to_c, enum_c);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Redefined)]
+#[redefined_attr(derive(Debug, PartialEq, Clone, Copy))]
+pub enum DiscriminantEnum {
+    A = 1,
+    B = 5,
+    C,
+}
+
+#[test]
+fn test_new_enum_discriminants() {
+    for enum_a in [DiscriminantEnum::A, DiscriminantEnum::B, DiscriminantEnum::C] {
+        let enum_b: DiscriminantEnumRedefined = enum_a.into();
+        assert_eq!(enum_b as u8, enum_a as u8);
+        let enum_b_to_a: DiscriminantEnum = enum_b.into();
+        assert_eq!(enum_b_to_a, enum_a);
+    }
+}
+
+/*
+
+
+
+
+
+Enum From A Full Path
+*/
+#[derive(Debug, Clone, PartialEq, Redefined)]
+#[redefined(redefined_test_types::enums::ComplexEnumA)]
+pub enum ComplexEnumC {
+    A(u64),
+    C { value: Vec<BasicStructB> },
+}
+
+#[test]
+fn test_path_enum() {
+    let enum_a = ComplexOutsideEnumA::C { value: vec![BasicStruct::default()] };
+    let enum_c: ComplexEnumC = enum_a.clone().into();
+    let enum_c_to_a: ComplexOutsideEnumA = enum_c.into();
+    assert_eq!(enum_c_to_a, enum_a);
+}
+
+/*
+
+
+
+
+
+Enum Field Attributes
+- `src` is the value of the variant's field
+*/
+pub enum CallbackEnum {
+    Value(u64),
+    Callback { callback: Box<dyn Fn(u64) -> u64 + Send>, val: u64 },
+}
+
+#[derive(Redefined)]
+#[redefined(CallbackEnum)]
+pub enum CallbackEnumA {
+    Value(#[redefined(func = "src * 10")] u64),
+    Callback {
+        #[redefined(from_source = "src", to_source = "src")]
+        callback: Box<dyn Fn(u64) -> u64 + Send>,
+        val:      u64,
+    },
+}
+
+/// new type with the callback kept unchanged
+#[derive(Redefined)]
+pub enum CallbackEnumB {
+    Value(u64),
+    Callback(#[redefined(from_source = "src", to_source = "src")] Box<dyn Fn(u64) -> u64 + Send>),
+}
+
+#[test]
+fn test_enum_field_attributes() {
+    let enum_a: CallbackEnumA = CallbackEnum::Value(1).into();
+    assert!(matches!(enum_a, CallbackEnumA::Value(10)));
+    let enum_a_to_source: CallbackEnum = enum_a.into();
+    assert!(matches!(enum_a_to_source, CallbackEnum::Value(10)));
+
+    let enum_a: CallbackEnumA = CallbackEnum::Callback { callback: Box::new(|x| x * 2), val: 3 }.into();
+    let CallbackEnumA::Callback { callback, val } = &enum_a else { panic!("expected the callback variant") };
+    assert_eq!(callback(*val), 6);
+
+    let CallbackEnum::Callback { callback, val } = enum_a.into() else { panic!("expected the callback variant") };
+    assert_eq!(callback(val), 6);
+}
+
+#[test]
+fn test_new_enum_field_attributes() {
+    let enum_b_redefined: CallbackEnumBRedefined = CallbackEnumB::Callback(Box::new(|x| x + 1)).into();
+    let CallbackEnumBRedefined::Callback(callback) = &enum_b_redefined else { panic!("expected the callback variant") };
+    assert_eq!(callback(1), 2);
+
+    let CallbackEnumB::Callback(callback) = enum_b_redefined.into() else { panic!("expected the callback variant") };
+    assert_eq!(callback(2), 3);
+
+    let enum_b_redefined: CallbackEnumBRedefined = CallbackEnumB::Value(4).into();
+    assert!(matches!(enum_b_redefined, CallbackEnumBRedefined::Value(4)));
+}
+
+mod derive_multi_source {
+    use super::*;
+
+    mod v1 {
+        /// codec of the first crate version
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Codec {
+            None,
+            Named(String),
+        }
+    }
+
+    mod v2 {
+        /// codec of the second crate version
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum Codec {
+            None,
+            Named(String),
+        }
+    }
+
+    /// enum with multiple sources and a per-source field attribute
+    #[derive(Debug, Clone, PartialEq, Redefined)]
+    #[redefined(v1::Codec, v2::Codec)]
+    pub enum CodecA {
+        None,
+        Named(#[redefined(func = "src.to_uppercase()", source = v2::Codec)] String),
+    }
+
+    #[test]
+    fn test_enum_multi_source_field_attribute() {
+        let enum_a: CodecA = v1::Codec::Named("lz4".to_string()).into();
+        assert_eq!(enum_a, CodecA::Named("lz4".to_string()));
+        let enum_a_to_v1: v1::Codec = enum_a.into();
+        assert_eq!(enum_a_to_v1, v1::Codec::Named("lz4".to_string()));
+
+        let enum_a: CodecA = v2::Codec::Named("zstd".to_string()).into();
+        assert_eq!(enum_a, CodecA::Named("ZSTD".to_string()));
+
+        let enum_a: CodecA = v2::Codec::None.into();
+        assert_eq!(enum_a, CodecA::None);
+    }
+}
@@ -0,0 +1,109 @@
+use redefined::Redefined;
+
+use crate::struct_test;
+
+pub trait Assoc {
+    type Value;
+}
+
+impl Assoc for u64 {
+    type Value = u64;
+}
+
+/// struct with const, lifetime and type generics with bounds
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundedGenericStruct<'a, 'b: 'a, const N: usize, X: Clone, Y>
+where
+    X: From<Y>,
+    Y: Assoc<Value = u64> + Clone,
+{
+    pub p: &'a u64,
+    pub d: &'b [i128; N],
+    pub x: X,
+    pub y: Vec<Y>,
+}
+
+impl<'a, 'b: 'a> Default for BoundedGenericStruct<'a, 'b, 10, u128, u64> {
+    fn default() -> Self {
+        Self { p: &100, d: &[1; 10], x: 200, y: vec![1, 2, 3] }
+    }
+}
+
+mod derive_source {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Redefined)]
+    #[redefined(BoundedGenericStruct)]
+    pub struct BoundedGenericStructA<'a, 'b: 'a, const N: usize, X: Clone, Y>
+    where
+        X: From<Y>,
+        Y: Assoc<Value = u64> + Clone,
+    {
+        pub p: &'a u64,
+        pub d: &'b [i128; N],
+        pub x: X,
+        pub y: Vec<Y>,
+    }
+
+    /// enum with type generics with bounds
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum BoundedGenericEnum<'a, X>
+    where
+        X: Assoc<Value = u64>,
+    {
+        A(&'a u64),
+        B { x: X },
+    }
+
+    #[derive(Debug, Clone, PartialEq, Redefined)]
+    #[redefined(BoundedGenericEnum)]
+    pub enum BoundedGenericEnumA<'a, X>
+    where
+        X: Assoc<Value = u64>,
+    {
+        A(&'a u64),
+        B { x: X },
+    }
+
+    #[test]
+    fn test_struct_bounded_generics() {
+        let struct_a = BoundedGenericStruct::default();
+        let struct_b: BoundedGenericStructA<10, u128, u64> = struct_a.clone().into();
+        let struct_b_to_a: BoundedGenericStruct<10, u128, u64> = struct_b.into();
+        assert_eq!(struct_b_to_a, struct_a);
+    }
+
+    #[test]
+    fn test_enum_bounded_generics() {
+        for enum_a in [BoundedGenericEnum::A(&10), BoundedGenericEnum::B { x: 20u64 }] {
+            let enum_b: BoundedGenericEnumA<u64> = enum_a.clone().into();
+            let enum_b_to_a: BoundedGenericEnum<u64> = enum_b.into();
+            assert_eq!(enum_b_to_a, enum_a);
+        }
+    }
+}
+
+mod derive_no_source {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Redefined)]
+    #[redefined_attr(derive(Debug, Clone, PartialEq))]
+    pub struct BoundedGenericStructA<'a, 'b: 'a, const N: usize, X: Clone, Y>
+    where
+        X: From<Y>,
+        Y: Assoc<Value = u64> + Clone,
+    {
+        pub p: &'a u64,
+        pub d: &'b [i128; N],
+        pub x: X,
+        pub y: Vec<Y>,
+    }
+
+    impl<'a, 'b: 'a> Default for BoundedGenericStructA<'a, 'b, 10, u128, u64> {
+        fn default() -> Self {
+            Self { p: &100, d: &[1; 10], x: 200, y: vec![1, 2, 3] }
+        }
+    }
+
+    struct_test!((BoundedGenericStructARedefined, 10, u128, u64), BoundedGenericStructA, { BoundedGenericStructA::default() });
+}
@@ -1,4 +1,4 @@
 pub mod attributes;
-//pub mod enums;
-//pub mod generics;
+pub mod enums;
+pub mod generics;
 pub mod structs;
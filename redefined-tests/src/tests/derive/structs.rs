@@ -3,7 +3,7 @@ use redefined_test_types::structs::*;
 
 use crate::struct_test;
 
-mod derive_source {
+pub mod derive_source {
     use super::*;
 
     /// basic struct
@@ -19,8 +19,8 @@ mod derive_source {
     #[derive(Debug, Clone, PartialEq, Default, Redefined)]
     #[redefined(GenericTypeStruct)]
     pub struct GenericTypeStructA<X, Y> {
-        pub p: u64,
-        pub d: X,
+        pub p:    u64,
+        pub d:    X,
         pub vals: Vec<Y>,
     }
 
@@ -50,7 +50,7 @@ mod derive_source {
     #[derive(Debug, Clone, PartialEq, Default, Redefined)]
     #[redefined(ComplexStructA)]
     pub struct ComplexStructAA<'a, 'b> {
-        pub n: i128,
+        pub n:       i128,
         pub inner_a: GenericLifetimeStructA<'a, 'b>,
         pub inner_b: Vec<BasicStructA>,
     }
@@ -77,8 +77,8 @@ mod derive_no_source {
     /// struct with type generics
     #[derive(Debug, Clone, PartialEq, Default, Redefined)]
     pub struct GenericTypeStructA<X, Y> {
-        pub p: u64,
-        pub d: X,
+        pub p:    u64,
+        pub d:    X,
         pub vals: Vec<Y>,
     }
 
@@ -119,7 +119,7 @@ mod derive_no_source {
     #[derive(Debug, Clone, PartialEq, Default, Redefined)]
     #[redefined_attr(derive(Debug, Clone, PartialEq))]
     pub struct ComplexStructAA<'a, 'b> {
-        pub n: i128,
+        pub n:       i128,
         #[redefined(field((GenericLifetimeStructA, default)))]
         pub inner_a: GenericLifetimeStructA<'a, 'b>,
         #[redefined(field((BasicStructA, BasicStructARedefined)))]
@@ -132,3 +132,932 @@ mod derive_no_source {
     struct_test!(GenericLifetimeStructARedefined, GenericLifetimeStructA);
     struct_test!(ComplexStructAARedefined, ComplexStructAA);
 }
+
+mod derive_source_path {
+    use super::*;
+
+    mod other {
+        /// basic struct with the same name as
+        /// `redefined_test_types::structs::BasicStruct`
+        #[derive(Debug, Clone, PartialEq, Default)]
+        pub struct BasicStruct {
+            pub val1: u64,
+            pub val2: Vec<u64>,
+        }
+    }
+
+    /// basic struct from a full path
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined(redefined_test_types::structs::BasicStruct)]
+    pub struct BasicStructA {
+        pub val1: u64,
+        pub val2: f64,
+        pub val3: String,
+    }
+
+    /// basic struct with the same name from another module
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined(other::BasicStruct)]
+    pub struct OtherBasicStructA {
+        pub val1: u64,
+        pub val2: Vec<u64>,
+    }
+
+    /// struct with constant generics from an instantiated path
+    #[derive(Debug, Clone, PartialEq, Redefined)]
+    #[redefined(redefined_test_types::structs::GenericConstantStruct<100>)]
+    pub struct GenericConstantStructA {
+        pub p: u64,
+        pub d: [i128; 100],
+    }
+
+    /// struct with lifetime generics from a path with generic arguments
+    #[derive(Debug, Clone, PartialEq, Redefined)]
+    #[redefined(redefined_test_types::structs::GenericLifetimeStruct<'a, 'b>)]
+    pub struct GenericLifetimeStructA<'a, 'b> {
+        pub p: &'a u64,
+        pub d: &'b [i128; 10],
+    }
+
+    struct_test!(BasicStructA, BasicStruct);
+    struct_test!(GenericLifetimeStructA, GenericLifetimeStruct);
+
+    #[test]
+    fn test_struct_same_name_other_module() {
+        let struct_a = other::BasicStruct { val1: 10, val2: vec![1, 2, 3] };
+        let struct_b: OtherBasicStructA = struct_a.clone().into();
+        let struct_b_to_a: other::BasicStruct = struct_b.into();
+        assert_eq!(struct_b_to_a, struct_a);
+    }
+
+    #[test]
+    fn test_struct_instantiated_constant_path() {
+        let struct_a = GenericConstantStruct::new([2; 100]);
+        let struct_b: GenericConstantStructA = struct_a.clone().into();
+        let struct_b_to_a: GenericConstantStruct<100> = struct_b.into();
+        assert_eq!(struct_b_to_a, struct_a);
+    }
+}
+
+mod derive_source_instantiated {
+    use super::*;
+
+    /// non-generic struct from a generic source
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined(GenericTypeStruct<u128, String>)]
+    pub struct PoolStateA {
+        pub p:    u64,
+        pub d:    u128,
+        pub vals: Vec<String>,
+    }
+
+    /// struct keeping a subset of the source's generics
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined(GenericTypeStruct<u64, Y>)]
+    pub struct PartialGenericTypeStructA<Y> {
+        pub p:    u64,
+        pub d:    u64,
+        pub vals: Vec<Y>,
+    }
+
+    /// struct with the source's generics reordered
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined(GenericTypeStruct<X, Y>)]
+    pub struct SwappedGenericTypeStructA<Y, X> {
+        pub p:    u64,
+        pub d:    X,
+        pub vals: Vec<Y>,
+    }
+
+    #[test]
+    fn test_struct_concrete_instantiation() {
+        let struct_a = GenericTypeStruct::new(100u128, vec!["a".to_string()]);
+        let struct_b: PoolStateA = struct_a.clone().into();
+        let struct_b_to_a: GenericTypeStruct<u128, String> = struct_b.into();
+        assert_eq!(struct_b_to_a, struct_a);
+    }
+
+    #[test]
+    fn test_struct_partial_instantiation() {
+        let struct_a = GenericTypeStruct::new(100u64, vec![true, false]);
+        let struct_b: PartialGenericTypeStructA<bool> = struct_a.clone().into();
+        let struct_b_to_a: GenericTypeStruct<u64, bool> = struct_b.into();
+        assert_eq!(struct_b_to_a, struct_a);
+    }
+
+    #[test]
+    fn test_struct_reordered_generics() {
+        let struct_a = GenericTypeStruct::new("d".to_string(), vec![1i32, 2]);
+        let struct_b: SwappedGenericTypeStructA<i32, String> = struct_a.clone().into();
+        let struct_b_to_a: GenericTypeStruct<String, i32> = struct_b.into();
+        assert_eq!(struct_b_to_a, struct_a);
+    }
+}
+
+mod derive_multi_source {
+    use super::*;
+
+    mod v1 {
+        /// compression settings of the first crate version
+        #[derive(Debug, Clone, PartialEq, Default)]
+        pub struct Compression {
+            pub level: u32,
+            pub name:  String,
+        }
+    }
+
+    mod v2 {
+        /// compression settings of the second crate version
+        #[derive(Debug, Clone, PartialEq, Default)]
+        pub struct Compression {
+            pub level: u32,
+            pub name:  String,
+        }
+    }
+
+    /// struct with multiple sources in one attribute
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined(v1::Compression, v2::Compression)]
+    pub struct CompressionA {
+        pub level: u32,
+        pub name:  String,
+    }
+
+    /// struct with multiple sources in repeated attributes and a per-source
+    /// field attribute
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined(v1::Compression)]
+    #[redefined(v2::Compression)]
+    pub struct CompressionB {
+        pub level: u32,
+        #[redefined(func = "src.name.to_uppercase()", source = v2::Compression)]
+        pub name:  String,
+    }
+
+    #[test]
+    fn test_struct_multi_source() {
+        let struct_v1 = v1::Compression { level: 1, name: "lz4".to_string() };
+        let struct_a: CompressionA = struct_v1.clone().into();
+        let struct_a_to_v1: v1::Compression = struct_a.into();
+        assert_eq!(struct_a_to_v1, struct_v1);
+
+        let struct_v2 = v2::Compression { level: 2, name: "zstd".to_string() };
+        let struct_a: CompressionA = struct_v2.clone().into();
+        let struct_a_to_v2: v2::Compression = struct_a.into();
+        assert_eq!(struct_a_to_v2, struct_v2);
+    }
+
+    #[test]
+    fn test_struct_multi_source_field_attribute() {
+        let struct_b: CompressionB = v1::Compression { level: 1, name: "lz4".to_string() }.into();
+        assert_eq!(struct_b.name, "lz4");
+
+        let struct_b: CompressionB = v2::Compression { level: 2, name: "zstd".to_string() }.into();
+        assert_eq!(struct_b.name, "ZSTD");
+    }
+
+    /// struct versioned by a constant generic
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Versioned<const VERSION: u8> {
+        pub level: u32,
+    }
+
+    /// struct with instantiations of the same source and a field attribute for
+    /// one of them
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined(Versioned<1>, Versioned<2>)]
+    pub struct VersionedA {
+        #[redefined(func = "src.level * 10", source = Versioned<2>)]
+        pub level: u32,
+    }
+
+    #[test]
+    fn test_struct_multi_source_generic_field_attribute() {
+        let struct_a: VersionedA = Versioned::<1> { level: 1 }.into();
+        assert_eq!(struct_a.level, 1);
+
+        let struct_a: VersionedA = Versioned::<2> { level: 1 }.into();
+        assert_eq!(struct_a.level, 10);
+    }
+}
+
+mod derive_composed_source {
+    use super::*;
+
+    /// static config of a pool
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct PoolConfig {
+        pub fee:   u32,
+        pub token: String,
+    }
+
+    /// live state of a pool
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct PoolState {
+        pub reserve:   u128,
+        pub liquidity: Vec<u64>,
+    }
+
+    /// unnamed parts of a pool
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct PoolIds(pub u64, pub String);
+
+    /// struct composed from a config and a state
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined((PoolConfig, PoolState))]
+    pub struct PoolView {
+        pub fee:       u32,
+        pub token:     String,
+        #[redefined(from = 1)]
+        pub reserve:   u128,
+        #[redefined(from = 1)]
+        pub liquidity: Vec<u64>,
+    }
+
+    /// unnamed struct composed from unnamed parts
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined((PoolIds, PoolIds))]
+    pub struct PoolIdsView(u64, #[redefined(from = 1)] u64, String, #[redefined(from = 1)] String);
+
+    /// struct composed with a field function
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined((BasicStruct, PoolState))]
+    #[redefined_attr(to_source = "(BasicStruct { val1: self.val1, ..Default::default() }, PoolState { reserve: self.reserve, ..Default::default() })")]
+    pub struct BasicPoolView {
+        pub val1:    u64,
+        #[redefined(func = "src.1.reserve * 2")]
+        pub reserve: u128,
+    }
+
+    #[test]
+    fn test_struct_composed_source() {
+        let config = PoolConfig { fee: 30, token: "WETH".to_string() };
+        let state = PoolState { reserve: 1000, liquidity: vec![1, 2, 3] };
+
+        let view: PoolView = (config.clone(), state.clone()).into();
+        assert_eq!(view, PoolView { fee: 30, token: "WETH".to_string(), reserve: 1000, liquidity: vec![1, 2, 3] });
+
+        let view_to_parts: (PoolConfig, PoolState) = view.into();
+        assert_eq!(view_to_parts, (config, state));
+    }
+
+    #[test]
+    fn test_unnamed_struct_composed_source() {
+        let parts = (PoolIds(1, "a".to_string()), PoolIds(2, "b".to_string()));
+
+        let view: PoolIdsView = parts.clone().into();
+        assert_eq!(view, PoolIdsView(1, 2, "a".to_string(), "b".to_string()));
+
+        let view_to_parts: (PoolIds, PoolIds) = view.into();
+        assert_eq!(view_to_parts, parts);
+    }
+
+    #[test]
+    fn test_struct_composed_source_field_fn() {
+        let basic = BasicStruct { val1: 5, ..Default::default() };
+        let state = PoolState { reserve: 1000, liquidity: vec![1] };
+
+        let view: BasicPoolView = (basic, state).into();
+        assert_eq!(view, BasicPoolView { val1: 5, reserve: 2000 });
+    }
+}
+
+mod derive_passthrough_types {
+    use super::*;
+
+    /// struct with a callback and a raw pointer
+    pub struct CallbackStruct {
+        pub val:      u64,
+        pub callback: Box<dyn Fn(u64) -> u64 + Send>,
+        pub ptr:      *const u8,
+    }
+
+    /// struct with a custom conversion for the callback
+    #[derive(Redefined)]
+    #[redefined(CallbackStruct)]
+    pub struct CallbackStructA {
+        pub val:      u64,
+        #[redefined(from_source = "src.callback", to_source = "self.callback")]
+        pub callback: Box<dyn Fn(u64) -> u64 + Send>,
+        pub ptr:      *const u8,
+    }
+
+    /// new type with the callback and raw pointer kept unchanged
+    #[derive(Redefined)]
+    pub struct CallbackStructB {
+        pub val:      u64,
+        #[redefined(from_source = "src.callback", to_source = "self.callback")]
+        pub callback: Box<dyn Fn(u64) -> u64 + Send>,
+        pub ptr:      *const u8,
+    }
+
+    macro_rules! group_type_struct {
+        ($ty:ty) => {
+            /// new type with a field type from a macro fragment
+            #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+            #[redefined_attr(derive(Debug, Clone, PartialEq, Default))]
+            pub struct GroupTypeStruct {
+                pub val:  $ty,
+                pub vals: Vec<$ty>,
+            }
+        };
+    }
+
+    group_type_struct!(u64);
+
+    #[test]
+    fn test_struct_callback() {
+        let byte = 10u8;
+        let callback = CallbackStruct { val: 1, callback: Box::new(|x| x * 2), ptr: &byte };
+
+        let callback_a: CallbackStructA = callback.into();
+        assert_eq!((callback_a.callback)(callback_a.val), 2);
+
+        let callback_a_to_source: CallbackStruct = callback_a.into();
+        assert_eq!((callback_a_to_source.callback)(4), 8);
+        assert_eq!(callback_a_to_source.ptr, &byte as *const u8);
+    }
+
+    #[test]
+    fn test_new_struct_callback() {
+        let byte = 10u8;
+        let callback = CallbackStructB { val: 1, callback: Box::new(|x| x + 1), ptr: &byte };
+
+        let callback_redefined: CallbackStructBRedefined = callback.into();
+        assert_eq!((callback_redefined.callback)(callback_redefined.val), 2);
+
+        let callback_redefined_to_b: CallbackStructB = callback_redefined.into();
+        assert_eq!(callback_redefined_to_b.ptr, &byte as *const u8);
+    }
+
+    #[test]
+    fn test_new_struct_group_type() {
+        let group = GroupTypeStruct { val: 1, vals: vec![2, 3] };
+
+        let group_redefined: GroupTypeStructRedefined = group.clone().into();
+        let group_redefined_to_group: GroupTypeStruct = group_redefined.into();
+        assert_eq!(group_redefined_to_group, group);
+    }
+}
+
+mod derive_path_types {
+    use std::{collections::HashMap, marker::PhantomData, sync::Arc, time::Duration};
+
+    use super::*;
+
+    pub mod inner {
+        use super::*;
+
+        /// struct in a module
+        #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+        #[redefined_attr(derive(Debug, Clone, PartialEq, Default))]
+        pub struct PathInner {
+            pub val: u64,
+        }
+
+        /// struct with const generics
+        #[derive(Debug, Clone, PartialEq, Redefined)]
+        #[redefined_attr(derive(Debug, Clone, PartialEq))]
+        pub struct Limbs<const N: usize>(pub [u64; N]);
+
+        /// struct with the name of a std type
+        #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+        #[redefined_attr(derive(Debug, Clone, PartialEq, Default))]
+        pub struct Duration {
+            pub secs: u64,
+        }
+    }
+
+    /// the new type of a type from another crate
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined(BasicStruct)]
+    pub struct BasicStructRedefined {
+        pub val1: u64,
+        pub val2: f64,
+        pub val3: String,
+    }
+
+    pub trait Assoc {
+        type Value;
+    }
+
+    pub struct Wrapper;
+
+    impl Assoc for Wrapper {
+        type Value = u64;
+    }
+
+    /// struct with module paths, std paths and qualified paths
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined_attr(derive(Debug, Clone, PartialEq, Default))]
+    pub struct PathTypesStruct {
+        pub inner:  inner::PathInner,
+        pub inners: std::vec::Vec<inner::PathInner>,
+        pub opt:    std::option::Option<self::inner::PathInner>,
+        pub prim:   std::primitive::u64,
+        pub assoc:  <Wrapper as Assoc>::Value,
+    }
+
+    /// struct with a const generic argument
+    #[derive(Debug, Clone, PartialEq, Redefined)]
+    #[redefined_attr(derive(Debug, Clone, PartialEq))]
+    pub struct UintLike<const N: usize> {
+        pub limbs: inner::Limbs<N>,
+    }
+
+    #[test]
+    fn test_new_struct_path_types() {
+        let path_types = PathTypesStruct {
+            inner:  inner::PathInner { val: 1 },
+            inners: vec![inner::PathInner { val: 2 }],
+            opt:    Some(inner::PathInner { val: 3 }),
+            prim:   4,
+            assoc:  5,
+        };
+
+        let path_types_redefined: PathTypesStructRedefined = path_types.clone().into();
+        let _: inner::PathInnerRedefined = path_types_redefined.inner.clone();
+        let path_types_redefined_to_path_types: PathTypesStruct = path_types_redefined.into();
+        assert_eq!(path_types_redefined_to_path_types, path_types);
+    }
+
+    /// struct with a type named like a std type and a type from another crate
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined_attr(derive(Debug, Clone, PartialEq, Default))]
+    pub struct ForeignPathsStruct {
+        pub duration: inner::Duration,
+        pub basic:    redefined_test_types::structs::BasicStruct,
+    }
+
+    #[test]
+    fn test_new_struct_foreign_paths() {
+        let foreign_paths = ForeignPathsStruct { duration: inner::Duration { secs: 1 }, basic: BasicStruct::default() };
+
+        let foreign_paths_redefined: ForeignPathsStructRedefined = foreign_paths.clone().into();
+        let _: inner::DurationRedefined = foreign_paths_redefined.duration.clone();
+        let _: BasicStructRedefined = foreign_paths_redefined.basic.clone();
+        let foreign_paths_redefined_to_foreign_paths: ForeignPathsStruct = foreign_paths_redefined.into();
+        assert_eq!(foreign_paths_redefined_to_foreign_paths, foreign_paths);
+    }
+
+    /// struct with std types used by their name
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined_attr(derive(Debug, Clone, PartialEq, Default))]
+    pub struct StdNamesStruct {
+        pub map:      HashMap<u64, inner::PathInner>,
+        #[redefined(from_source = "Arc::new((*src.shared).clone().into())", to_source = "Arc::new((*self.shared).clone().into())")]
+        pub shared:   Arc<inner::PathInner>,
+        #[redefined(from_source = "PhantomData", to_source = "PhantomData")]
+        pub marker:   PhantomData<inner::PathInner>,
+        #[redefined(from_source = "src.duration", to_source = "self.duration")]
+        pub duration: Duration,
+    }
+
+    #[test]
+    fn test_new_struct_std_names() {
+        let std_names = StdNamesStruct {
+            map:      HashMap::from([(1, inner::PathInner { val: 2 })]),
+            shared:   Arc::new(inner::PathInner { val: 3 }),
+            marker:   PhantomData,
+            duration: Duration::from_secs(4),
+        };
+
+        let std_names_redefined: StdNamesStructRedefined = std_names.clone().into();
+        let _: HashMap<u64, inner::PathInnerRedefined> = std_names_redefined.map.clone();
+        let _: Arc<inner::PathInnerRedefined> = std_names_redefined.shared.clone();
+        let _: PhantomData<inner::PathInnerRedefined> = std_names_redefined.marker;
+        let _: Duration = std_names_redefined.duration;
+        let std_names_redefined_to_std_names: StdNamesStruct = std_names_redefined.into();
+        assert_eq!(std_names_redefined_to_std_names, std_names);
+    }
+
+    mod shared_std_name {
+        use super::{
+            inner::{Duration, DurationRedefined},
+            *,
+        };
+
+        /// struct with a type sharing the name of a std type
+        #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+        #[redefined_attr(derive(Debug, Clone, PartialEq, Default))]
+        #[redefined_attr(map_types(Duration = default))]
+        pub struct SharedStdNameStruct {
+            pub duration: Duration,
+        }
+
+        #[test]
+        fn test_new_struct_shared_std_name() {
+            let shared_std_name = SharedStdNameStruct { duration: Duration { secs: 1 } };
+
+            let shared_std_name_redefined: SharedStdNameStructRedefined = shared_std_name.clone().into();
+            let _: DurationRedefined = shared_std_name_redefined.duration.clone();
+            let shared_std_name_redefined_to_shared_std_name: SharedStdNameStruct = shared_std_name_redefined.into();
+            assert_eq!(shared_std_name_redefined_to_shared_std_name, shared_std_name);
+        }
+    }
+
+    #[test]
+    fn test_new_struct_const_generic_path() {
+        let uint = UintLike { limbs: inner::Limbs([1, 2, 3, 4]) };
+
+        let uint_redefined: UintLikeRedefined<4> = uint.clone().into();
+        let _: inner::LimbsRedefined<4> = uint_redefined.limbs.clone();
+        let uint_redefined_to_uint: UintLike<4> = uint_redefined.into();
+        assert_eq!(uint_redefined_to_uint, uint);
+    }
+}
+
+mod derive_type_map {
+    use redefined::RedefinedConvert;
+
+    use super::*;
+
+    pub mod other {
+        #[derive(Debug, Clone, PartialEq, Default)]
+        pub struct Address(pub [u8; 4]);
+
+        redefined::self_convert_redefined!(Address);
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Amount<const N: usize>(pub [u64; N]);
+
+    impl<const N: usize> Default for Amount<N> {
+        fn default() -> Self {
+            Self([0; N])
+        }
+    }
+
+    impl<const N: usize> RedefinedConvert<Amount<N>> for Amount<N> {
+        fn from_source(src: Amount<N>) -> Self {
+            src
+        }
+
+        fn to_source(self) -> Amount<N> {
+            self
+        }
+    }
+
+    /// mirror of a concrete instance of a generic type
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined(Amount<2>)]
+    pub struct AmountMirror(pub [u64; 2]);
+
+    /// new type with container type mappings, the field mapping takes
+    /// precedence
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined_attr(derive(Debug, Clone, PartialEq, Default))]
+    #[redefined_attr(map_types(other::Address = same, Amount<2> = AmountMirror))]
+    pub struct Account {
+        pub address:     other::Address,
+        pub balance:     Amount<2>,
+        pub balances:    Vec<Amount<2>>,
+        #[redefined(field((Amount, same)))]
+        pub raw_balance: Amount<2>,
+    }
+
+    /// new enum with container type mappings
+    #[derive(Debug, Clone, PartialEq, Redefined)]
+    #[redefined_attr(derive(Debug, Clone, PartialEq))]
+    #[redefined_attr(map_types(Address = same, Amount<2> = AmountMirror))]
+    pub enum AccountEvent {
+        Deposit(Amount<2>),
+        Moved { from: other::Address, to: other::Address },
+    }
+
+    #[test]
+    fn test_new_struct_type_map() {
+        let account = Account {
+            address:     other::Address([1, 2, 3, 4]),
+            balance:     Amount([1, 2]),
+            balances:    vec![Amount([3, 4])],
+            raw_balance: Amount([5, 6]),
+        };
+
+        let account_redefined: AccountRedefined = account.clone().into();
+        let _: (other::Address, AmountMirror, Vec<AmountMirror>, Amount<2>) = (
+            account_redefined.address.clone(),
+            account_redefined.balance.clone(),
+            account_redefined.balances.clone(),
+            account_redefined.raw_balance.clone(),
+        );
+
+        let account_redefined_to_account: Account = account_redefined.into();
+        assert_eq!(account_redefined_to_account, account);
+    }
+
+    #[test]
+    fn test_new_enum_type_map() {
+        for event in [AccountEvent::Deposit(Amount([1, 2])), AccountEvent::Moved { from: other::Address([1; 4]), to: other::Address([2; 4]) }] {
+            let event_redefined: AccountEventRedefined = event.clone().into();
+            if let AccountEventRedefined::Deposit(amount) = &event_redefined {
+                assert_eq!(amount, &AmountMirror([1, 2]));
+            }
+
+            let event_redefined_to_event: AccountEvent = event_redefined.into();
+            assert_eq!(event_redefined_to_event, event);
+        }
+    }
+}
+
+mod derive_config {
+    use super::*;
+
+    /// kept unchanged by the crate's `redefined.toml`
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct ConfigSameStruct {
+        pub val: u64,
+    }
+
+    redefined::self_convert_redefined!(ConfigSameStruct);
+
+    /// new type with a field mapped by the config
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined_attr(derive(Debug, Clone, PartialEq, Default))]
+    pub struct ConfigStruct {
+        pub inner:  ConfigSameStruct,
+        pub inners: Vec<ConfigSameStruct>,
+    }
+
+    #[test]
+    fn test_new_struct_config_type_map() {
+        let config = ConfigStruct { inner: ConfigSameStruct { val: 1 }, inners: vec![ConfigSameStruct { val: 2 }] };
+
+        let config_redefined: ConfigStructRedefined = config.clone().into();
+        let _: (ConfigSameStruct, Vec<ConfigSameStruct>) = (config_redefined.inner.clone(), config_redefined.inners.clone());
+
+        let config_redefined_to_config: ConfigStruct = config_redefined.into();
+        assert_eq!(config_redefined_to_config, config);
+    }
+}
+
+mod derive_named {
+    use super::*;
+
+    /// new type with a given name
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined_attr(name = "NamedInnerMirror", derive(Debug, Clone, PartialEq, Default))]
+    pub struct NamedInner {
+        pub val: u64,
+    }
+
+    /// new type referencing a renamed type
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined_attr(name = "NamedOuterMirror", derive(Debug, Clone, PartialEq, Default))]
+    pub struct NamedOuter {
+        pub inner:  NamedInner,
+        pub inners: Vec<NamedInner>,
+    }
+
+    #[test]
+    fn test_new_struct_named() {
+        let outer = NamedOuter { inner: NamedInner { val: 1 }, inners: vec![NamedInner { val: 2 }] };
+
+        let outer_mirror: NamedOuterMirror = outer.clone().into();
+        assert_eq!(outer_mirror.inner, NamedInnerMirror { val: 1 });
+
+        let outer_mirror_to_outer: NamedOuter = outer_mirror.into();
+        assert_eq!(outer_mirror_to_outer, outer);
+    }
+}
+
+mod derive_named_ambiguous {
+    use super::*;
+
+    mod first {
+        use super::*;
+
+        #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+        #[redefined_attr(name = "SameNamedFirst", derive(Debug, Clone, PartialEq, Default))]
+        pub struct SameNamed {
+            pub val: u64,
+        }
+    }
+
+    mod second {
+        use super::*;
+
+        #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+        #[redefined_attr(name = "SameNamedSecond", derive(Debug, Clone, PartialEq, Default))]
+        pub struct SameNamed {
+            pub val: u64,
+        }
+    }
+
+    /// `SameNamed` matches both new types, so they're mapped
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined_attr(
+        derive(Debug, Clone, PartialEq, Default),
+        map_types(first::SameNamed = first::SameNamedFirst, second::SameNamed = second::SameNamedSecond)
+    )]
+    pub struct SameNamedOuter {
+        pub first:  first::SameNamed,
+        pub second: second::SameNamed,
+    }
+
+    #[test]
+    fn test_new_struct_named_ambiguous() {
+        let outer = SameNamedOuter { first: first::SameNamed { val: 1 }, second: second::SameNamed { val: 2 } };
+
+        let outer_redefined: SameNamedOuterRedefined = outer.clone().into();
+        assert_eq!((outer_redefined.first.val, outer_redefined.second.val), (1, 2));
+
+        let outer_redefined_to_outer: SameNamedOuter = outer_redefined.into();
+        assert_eq!(outer_redefined_to_outer, outer);
+    }
+}
+
+mod derive_visibility {
+    use super::*;
+
+    mod private {
+        use super::*;
+
+        /// struct with private fields
+        #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+        #[redefined_attr(derive(Debug, Clone, PartialEq, Default), vis = "pub(crate)", field_vis = "pub")]
+        pub struct PrivateFields {
+            val:  u64,
+            vals: Vec<u64>,
+        }
+
+        impl PrivateFields {
+            pub fn new(val: u64, vals: Vec<u64>) -> Self {
+                Self { val, vals }
+            }
+        }
+
+        /// unnamed struct with private fields
+        #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+        #[redefined_attr(derive(Debug, Clone, PartialEq, Default), field_vis = "pub")]
+        pub struct PrivateUnnamedFields(u64, String);
+
+        impl PrivateUnnamedFields {
+            pub fn new(val: u64, name: String) -> Self {
+                Self(val, name)
+            }
+        }
+    }
+
+    #[test]
+    fn test_new_struct_visibility() {
+        let private = private::PrivateFields::new(1, vec![2, 3]);
+
+        let private_redefined: private::PrivateFieldsRedefined = private.clone().into();
+        assert_eq!((private_redefined.val, &private_redefined.vals), (1, &vec![2, 3]));
+
+        let private_redefined_to_private: private::PrivateFields = private_redefined.into();
+        assert_eq!(private_redefined_to_private, private);
+    }
+
+    #[test]
+    fn test_new_unnamed_struct_visibility() {
+        let private = private::PrivateUnnamedFields::new(1, "a".to_string());
+
+        let private_redefined: private::PrivateUnnamedFieldsRedefined = private.clone().into();
+        assert_eq!((private_redefined.0, private_redefined.1.as_str()), (1, "a"));
+    }
+}
+
+mod derive_module {
+    use super::*;
+
+    /// struct generated in a module
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined_attr(derive(Debug, Clone, PartialEq, Default), module = "mirror_inner")]
+    pub struct ModuleInner {
+        pub val: u64,
+    }
+
+    /// private struct generated in a module, referencing a type in another
+    /// module
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined_attr(derive(Debug, Clone, PartialEq, Default), module = "mirror_outer")]
+    struct ModuleOuter {
+        inner:  ModuleInner,
+        inners: Vec<ModuleInner>,
+    }
+
+    /// enum generated in a module
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined_attr(derive(Debug, Clone, PartialEq, Default), module = "mirror_enum")]
+    pub enum ModuleEnum {
+        #[default]
+        A,
+        B(ModuleInner),
+    }
+
+    /// struct in the calling module, referencing types in modules
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined_attr(derive(Debug, Clone, PartialEq, Default))]
+    pub struct ModuleUser {
+        pub inner: ModuleInner,
+        pub e:     ModuleEnum,
+    }
+
+    #[test]
+    fn test_new_struct_module() {
+        let inner = ModuleInner { val: 1 };
+        let outer = ModuleOuter { inner: inner.clone(), inners: vec![inner.clone(), ModuleInner { val: 2 }] };
+
+        let outer_redefined: mirror_outer::ModuleOuterRedefined = outer.clone().into();
+        let inner_redefined: &mirror_inner::ModuleInnerRedefined = &outer_redefined.inner;
+        assert_eq!(inner_redefined.val, 1);
+        assert_eq!(outer_redefined.inners.len(), 2);
+
+        let outer_redefined_to_outer: ModuleOuter = outer_redefined.into();
+        assert_eq!(outer_redefined_to_outer, outer);
+    }
+
+    #[test]
+    fn test_new_enum_module() {
+        let user = ModuleUser { inner: ModuleInner { val: 1 }, e: ModuleEnum::B(ModuleInner { val: 2 }) };
+
+        let user_redefined: ModuleUserRedefined = user.clone().into();
+        assert_eq!(user_redefined.e, mirror_enum::ModuleEnumRedefined::B(mirror_inner::ModuleInnerRedefined { val: 2 }));
+
+        let user_redefined_to_user: ModuleUser = user_redefined.into();
+        assert_eq!(user_redefined_to_user, user);
+    }
+}
+
+mod derive_crate_path {
+    use super::*;
+
+    /// re-exports the crate under another name
+    pub mod facade {
+        pub use redefined as redefined_reexport;
+    }
+
+    /// source struct with a generic parameter named like a generated one
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct HygieneSource<T, TR> {
+        pub a: T,
+        pub b: TR,
+    }
+
+    /// struct using the crate through the facade
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined(HygieneSource)]
+    #[redefined_attr(crate = "crate::tests::derive::structs::derive_crate_path::facade::redefined_reexport")]
+    pub struct HygieneTarget<T, TR> {
+        pub a: T,
+        pub b: TR,
+    }
+
+    /// new struct using the crate through the facade
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined_attr(
+        derive(Debug, Clone, PartialEq, Default),
+        map_types(HygieneSource = HygieneTarget),
+        crate = "crate::tests::derive::structs::derive_crate_path::facade::redefined_reexport"
+    )]
+    pub struct CratePathStruct {
+        pub val:    u64,
+        pub source: HygieneSource<u64, String>,
+    }
+
+    /// new enum with named fields, using the crate through the facade
+    #[derive(Debug, Clone, PartialEq, Default, Redefined)]
+    #[redefined_attr(
+        derive(Debug, Clone, PartialEq, Default),
+        crate = "crate::tests::derive::structs::derive_crate_path::facade::redefined_reexport"
+    )]
+    pub enum CratePathEnum {
+        #[default]
+        A,
+        B {
+            src: u64,
+            tmp: String,
+        },
+    }
+
+    #[test]
+    fn test_struct_crate_path_generics() {
+        let source = HygieneSource { a: 1u64, b: "a".to_string() };
+
+        let target: HygieneTarget<u64, String> = source.clone().into();
+        assert_eq!((target.a, target.b.as_str()), (1, "a"));
+
+        let target_to_source: HygieneSource<u64, String> = target.into();
+        assert_eq!(target_to_source, source);
+    }
+
+    #[test]
+    fn test_new_type_crate_path() {
+        let val = CratePathStruct { val: 1, source: HygieneSource { a: 2, b: "b".to_string() } };
+
+        let val_redefined: CratePathStructRedefined = val.clone().into();
+        assert_eq!(val_redefined.source.a, 2);
+
+        let val_redefined_to_val: CratePathStruct = val_redefined.into();
+        assert_eq!(val_redefined_to_val, val);
+
+        let e = CratePathEnum::B { src: 1, tmp: "a".to_string() };
+        let e_redefined: CratePathEnumRedefined = e.clone().into();
+        assert_eq!(e_redefined, CratePathEnumRedefined::B { src: 1, tmp: "a".to_string() });
+
+        let e_redefined_to_e: CratePathEnum = e_redefined.into();
+        assert_eq!(e_redefined_to_e, e);
+    }
+}
@@ -13,7 +13,7 @@ mod crates_io {
     struct_test!((UintRedefined, 256, 4), Uint, { Uint::from_limbs([100; 4]) });
 }
 
-mod github {
+mod path_dependency {
     use super::*;
 
     redefined_remote!([BasicStruct] : "redefined-test-types");
@@ -49,3 +49,123 @@ mod derives {
         assert!(ser.is_ok())
     }
 }
+
+mod renamed {
+    use ruint::Uint;
+
+    use super::*;
+
+    redefined_remote!([Uint as U256Mirror] : "ruint");
+
+    struct_test!((U256Mirror, 256, 4), Uint, { Uint::from_limbs([100; 4]) });
+}
+
+mod visibility {
+    use super::*;
+
+    redefined_remote!(#[redefined_attr(vis = "pub(crate)", field_vis = "pub")] [PrivateFieldStruct] : "redefined-test-types");
+
+    #[test]
+    fn test_field_vis() {
+        let val: PrivateFieldStructRedefined = PrivateFieldStruct::default().into();
+        let _ = (val.p, val.d, val.vals);
+    }
+}
+
+mod module {
+    use super::*;
+
+    redefined_remote!(mod mirror { [BasicStruct, PrivateFieldStruct] : "redefined-test-types" });
+
+    use mirror::{BasicStructRedefined, PrivateFieldStructRedefined};
+
+    struct_test!(BasicStructRedefined, BasicStruct);
+    struct_test!(PrivateFieldStructRedefined, PrivateFieldStruct);
+}
+
+mod module_path {
+    use ruint::Uint;
+
+    use super::*;
+
+    redefined_remote!([ruint::Uint as U256PathMirror] : "ruint");
+
+    struct_test!((U256PathMirror, 256, 4), Uint, { Uint::from_limbs([100; 4]) });
+}
+
+mod reexport {
+    use ruint::Bits;
+
+    use super::*;
+
+    // `Bits` is defined in a private module of `ruint` and re-exported from it's
+    // root
+    redefined_remote!([ruint::Uint, ruint::Bits] : "ruint");
+
+    struct_test!((BitsRedefined, 256, 4), Bits, { Bits::from(ruint::Uint::from_limbs([100; 4])) });
+}
+
+mod alias {
+    use ruint::aliases::{U128, U64};
+
+    use super::*;
+
+    // `U128` is an alias of `ruint::Uint<128, 2>`, mirrored as an alias of
+    // `UintRedefined`
+    redefined_remote!([ruint::aliases::U128, ruint::Uint] : "ruint");
+
+    // `U64` is mirrored as a concrete new type
+    redefined_remote!(#[redefined_attr(concrete)] [ruint::aliases::U64 as U64Concrete] : "ruint");
+
+    struct_test!(U128Redefined, U128, { U128::from_limbs([100; 2]) });
+    struct_test!(U64Concrete, U64, { U64::from_limbs([100]) });
+}
+
+mod version_requirement {
+    use ruint::{Bits, Uint};
+
+    use super::*;
+
+    // the locked version of `ruint` matching the requirement
+    redefined_remote!([ruint::Uint, ruint::Bits] : "ruint@1.12");
+
+    struct_test!((BitsRedefined, 256, 4), Bits, { Bits::from(Uint::from_limbs([100; 4])) });
+}
+
+mod recursive {
+    use ruint::{Bits, Uint};
+
+    use super::*;
+
+    // mirrors `Uint`, the type of the field of `Bits`
+    redefined_remote!(#[redefined_attr(recursive)] [ruint::Bits] : "ruint");
+
+    struct_test!((BitsRedefined, 256, 4), Bits, { Bits::from(Uint::from_limbs([100; 4])) });
+    struct_test!((UintRedefined, 256, 4), Uint, { Uint::from_limbs([100; 4]) });
+}
+
+mod recursive_exclude {
+    use ruint::{Bits, Uint};
+
+    use super::*;
+
+    redefined_remote!([ruint::Uint] : "ruint");
+
+    // `Uint` is mirrored by the call above
+    redefined_remote!(#[redefined_attr(recursive, exclude(Uint))] [ruint::Bits] : "ruint");
+
+    struct_test!((BitsRedefined, 256, 4), Bits, { Bits::from(Uint::from_limbs([100; 4])) });
+}
+
+mod recursive_exclude_path {
+    use ruint::{Bits, Uint};
+
+    use super::*;
+
+    redefined_remote!([ruint::Uint] : "ruint");
+
+    // excluded by the end of it's full path
+    redefined_remote!(#[redefined_attr(recursive, exclude(ruint::Uint))] [ruint::Bits] : "ruint");
+
+    struct_test!((BitsRedefined, 256, 4), Bits, { Bits::from(Uint::from_limbs([100; 4])) });
+}
@@ -27,6 +27,26 @@ impl<'a, T> RedefinedConvert<&'a T> for &'a T {
     }
 }
 
+impl<T> RedefinedConvert<*const T> for *const T {
+    fn from_source(src: *const T) -> Self {
+        src
+    }
+
+    fn to_source(self) -> *const T {
+        self
+    }
+}
+
+impl<T> RedefinedConvert<*mut T> for *mut T {
+    fn from_source(src: *mut T) -> Self {
+        src
+    }
+
+    fn to_source(self) -> *mut T {
+        self
+    }
+}
+
 impl<T, F> RedefinedConvert<Option<T>> for Option<F>
 where
     F: RedefinedConvert<T>,
@@ -101,7 +121,7 @@ macro_rules! self_convert_redefined_with_fixed_size_array {
 macro_rules! self_convert_redefined {
     ($($val:ident),*) => {
         $(
-            impl redefined::RedefinedConvert<$val> for $val {
+            impl $crate::RedefinedConvert<$val> for $val {
                 fn from_source(item: $val) -> Self {
                     item
                 }